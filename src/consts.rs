@@ -1,15 +1,150 @@
 use std::time::Duration;
 
-pub const MAX_BLOCK_WIDTH: i32 = 10;
-pub const MAX_BLOCK_HEIGHT: i32 = 25;
-pub const MAX_STACK_HEIGHT: i32 = 20;
+// the size of a board in blocks; blocks above the stack height top out, rows above the visible
+// height are hidden, and minos spawn at the spawn position
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Dimensions {
+    pub width: i32,
+    pub height: i32,
+    pub visible_height: i32,
+    pub stack_height: i32,
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+}
+
+impl Dimensions {
+    // minos spawn centered on the stack height, two rows under the top of the view
+    pub const fn new(width: i32, visible_height: i32) -> Self {
+        Self {
+            width,
+            height: visible_height + 3,
+            visible_height,
+            stack_height: visible_height - 2,
+            spawn_x: (width - 4) / 2,
+            spawn_y: visible_height - 2,
+        }
+    }
+}
 
-pub const SPAWN_BLOCK_X: i32 = 3;
-pub const SPAWN_BLOCK_Y: i32 = 20;
+pub const DEFAULT_DIMENSIONS: Dimensions = Dimensions::new(10, 22);
+pub const MIN_BOARD_WIDTH: i32 = 4;
+pub const MIN_BOARD_HEIGHT: i32 = 8;
 
 pub const UPDATE_INTERVAL: Duration = Duration::from_millis(400);
+// how far F7 and F8 slow down and speed up the game clock
+pub const MIN_CLOCK_SCALE: f64 = 0.125;
+pub const MAX_CLOCK_SCALE: f64 = 8.0;
+pub const DEFAULT_ENTRY_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_LINE_CLEAR_DELAY: Duration = Duration::from_millis(300);
+
+// lines sent to the opponent indexed by the number of lines cleared at once
+pub const ATTACK_TABLE: &[i32] = &[0, 0, 1, 2, 4];
+pub const MAX_GARBAGE_PER_PLACE: i32 = 8;
+
+pub const NET_TICK_INTERVAL: Duration = Duration::from_micros(16_667);
+pub const NET_INPUT_DELAY: u32 = 3;
+// the most the rollback input delay and window may be set to, keeping the inputs resent to a
+// silent peer within NET_MAX_FRAME_LEN
+pub const NET_MAX_INPUT_DELAY: u32 = 30;
+pub const NET_MAX_ROLLBACK: u32 = 60;
+// how many board events are kept for observers that haven't read them yet
+pub const MAX_PENDING_EVENTS: usize = 64;
+// how much work a script hook may do before it's stopped
+pub const SCRIPT_MAX_OPERATIONS: u64 = 100_000;
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 32;
+pub const SCRIPT_MAX_EXPR_DEPTH: usize = 64;
+// how scattered the holes of the garbage raised by scripts are, in percent
+pub const SCRIPT_MESSINESS: u32 = 30;
+// the longest string, array and map a script may build
+pub const SCRIPT_MAX_SIZE: usize = 4096;
+pub const NET_TIMEOUT: Duration = Duration::from_secs(5);
+// the longest message a peer may send, well above the largest batch of inputs
+pub const NET_MAX_FRAME_LEN: usize = 8192;
+
+pub const ROYALE_MIN_BOTS: usize = 10;
+pub const ROYALE_MAX_BOTS: usize = 99;
+pub const ROYALE_DEFAULT_BOTS: usize = 49;
+pub const ROYALE_RETARGET_INTERVAL: Duration = Duration::from_secs(3);
+pub const ROYALE_BOT_THINK_TIME: std::ops::Range<Duration> =
+    Duration::from_millis(250)..Duration::from_millis(1200);
+
+// attack multiplier unlocked by the number of badges held
+pub const ROYALE_BADGE_MULTIPLIERS: &[(u32, f32)] = &[(2, 1.25), (6, 1.5), (14, 1.75), (30, 2.0)];
+
+pub const CHEESE_DEFAULT_LINES: i32 = 18;
+pub const CHEESE_VISIBLE_LINES: i32 = 10;
+
+// rows rise once per interval, and every row shortens the interval down to the minimum
+pub const SURVIVAL_START_INTERVAL: Duration = Duration::from_secs(8);
+pub const SURVIVAL_MIN_INTERVAL: Duration = Duration::from_millis(1500);
+pub const SURVIVAL_SPEEDUP: f32 = 0.95;
+pub const SURVIVAL_MESSINESS: u32 = 30;
+
+pub const PUZZLE_PREVIEW: usize = 5;
+// where solved puzzles are remembered, relative to the working directory
+pub const PUZZLE_PROGRESS_PATH: &str = "puzzle_progress.toml";
+
+// the buttons take this many columns left of the board
+pub const EDITOR_BUTTON_WIDTH: i32 = 9;
+// where the editor saves and loads its board, relative to the working directory
+pub const EDITOR_BOARD_PATH: &str = "board.toml";
+
+// the NES counts everything in frames of its NTSC refresh rate
+pub const NES_FRAME: Duration = Duration::from_nanos(16_639_267);
+// frames per row indexed by level, the last entry holding from the level 29 kill screen on
+#[rustfmt::skip]
+pub const NES_GRAVITY: &[u32] = &[
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6,
+    5, 5, 5, 4, 4, 4, 3, 3, 3, 2,
+    2, 2, 2, 2, 2, 2, 2, 2, 2, 1,
+];
+// points indexed by the number of lines cleared at once, multiplied by the level plus one
+pub const NES_LINE_SCORES: &[i32] = &[0, 40, 100, 300, 1200];
+pub const NES_DAS_DELAY: u32 = 16;
+pub const NES_DAS_REPEAT: u32 = 6;
+pub const NES_ENTRY_DELAY: u32 = 10;
+pub const NES_LINE_CLEAR_DELAY: u32 = 20;
+pub const NES_MAX_START_LEVEL: u32 = 19;
 
-#[derive(Clone)]
+// the arcade TGM boards refresh at about 61.68 frames a second
+pub const TGM_FRAME: Duration = Duration::from_nanos(16_212_710);
+pub const TGM_MAX_LEVEL: u32 = 999;
+pub const TGM_SECTION_LEVELS: u32 = 100;
+// gravity in 256ths of a row per frame from the level each entry starts at, 5120 being 20G
+#[rustfmt::skip]
+pub const TGM_GRAVITY: &[(u32, u32)] = &[
+    (0, 4), (30, 6), (35, 8), (40, 10), (50, 12), (60, 16), (70, 32), (80, 48), (90, 64),
+    (100, 80), (120, 96), (140, 112), (160, 128), (170, 144), (200, 4), (220, 32), (230, 64),
+    (233, 96), (236, 128), (239, 160), (243, 192), (247, 224), (251, 256), (300, 512),
+    (330, 768), (360, 1024), (400, 1280), (420, 1024), (450, 768), (500, 5120),
+];
+// ARE, line clear, lock delay and DAS in frames from the level each entry starts at
+#[rustfmt::skip]
+pub const TGM_DELAYS: &[(u32, [u32; 4])] = &[
+    (0, [25, 40, 30, 14]),
+    (500, [25, 25, 30, 8]),
+    (600, [25, 16, 30, 8]),
+    (700, [16, 12, 30, 8]),
+    (800, [12, 6, 30, 8]),
+    (900, [12, 6, 17, 6]),
+];
+pub const TGM_DAS_REPEAT: u32 = 1;
+// the score each grade needs, from 9 up to S9
+#[rustfmt::skip]
+pub const TGM_GRADES: &[(i32, &str)] = &[
+    (0, "9"), (400, "8"), (800, "7"), (1400, "6"), (2000, "5"), (3500, "4"), (5500, "3"),
+    (8000, "2"), (12000, "1"), (16000, "S1"), (22000, "S2"), (30000, "S3"), (40000, "S4"),
+    (52000, "S5"), (66000, "S6"), (82000, "S7"), (100000, "S8"), (120000, "S9"),
+];
+// the grand master grade needs each level reached within the time and with the score
+pub const TGM_GM_CHECKPOINTS: &[(u32, Duration, i32)] = &[
+    (300, Duration::from_secs(4 * 60 + 15), 12000),
+    (500, Duration::from_secs(7 * 60), 40000),
+    (999, Duration::from_secs(13 * 60 + 30), 120000),
+];
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BlockColor {
     Cyan,
     Yellow,
@@ -18,29 +153,31 @@ pub enum BlockColor {
     Blue,
     Orange,
     Purple,
+    Gray,
 }
 
-#[derive(Clone)]
-pub struct MinoTemplate {
-    pub blocks: &'static [(i32, i32)],
-    pub rotation_origin: (f32, f32),
-    pub color: BlockColor,
-}
+pub const TEXT_SCALE: f32 = 16.0;
 
-#[rustfmt::skip]
-pub const MINO_TEMPLATES: &[MinoTemplate] = &[
-    MinoTemplate { blocks: &[(0, 0), (1, 0), (2, 0), (3, 0)], rotation_origin: (1.5, 0.5), color: BlockColor::Cyan },    // I tetromino
-    MinoTemplate { blocks: &[(1, 0), (2, 0), (2, 1), (1, 1)], rotation_origin: (1.5, 0.5), color: BlockColor::Yellow },  // O tetromino
-    MinoTemplate { blocks: &[(0, 0), (1, 0), (1, 1), (2, 1)], rotation_origin: (1.0, 0.0), color: BlockColor::Green },   // S tetromino
-    MinoTemplate { blocks: &[(0, 1), (1, 1), (1, 0), (2, 0)], rotation_origin: (1.0, 0.0), color: BlockColor::Red },     // Z tetromino
-    MinoTemplate { blocks: &[(0, 1), (0, 0), (1, 0), (2, 0)], rotation_origin: (1.0, 0.0), color: BlockColor::Blue },    // J tetromino
-    MinoTemplate { blocks: &[(0, 0), (1, 0), (2, 0), (2, 1)], rotation_origin: (1.0, 0.0), color: BlockColor::Orange },  // L tetromino
-    MinoTemplate { blocks: &[(0, 0), (1, 0), (2, 0), (1, 1)], rotation_origin: (1.0, 0.0), color: BlockColor::Purple },  // T tetromino
-];
+// menu rows are this many blocks apart with at most this many on screen, centered this far above
+// the middle of the board and as wide as it for the cursor
+pub const MENU_ROW_HEIGHT: f32 = 1.25;
+pub const MENU_MAX_ROWS: usize = 13;
+pub const MENU_CENTER: f32 = -1.0;
+pub const MENU_WIDTH: f32 = 10.0;
+// the mode shortcuts listed under the main page on each line of its footer
+pub const MENU_SHORTCUTS_PER_ROW: usize = 5;
 
-pub const VIEW_WIDTH: f32 = 10.0;
-pub const VIEW_HEIGHT: f32 = 22.0;
-pub const TEXT_SCALE: f32 = 16.0;
+// where the best scores and the last games are kept, relative to the working directory
+pub const RECORDS_PATH: &str = "records.toml";
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_SAVED_REPLAYS: usize = 10;
+// where the piece set and rotation system picked in the menu are kept
+pub const SETTINGS_PATH: &str = "settings.toml";
+// the scripted modes offered in the menu
+pub const SCRIPTS_DIR: &str = "assets/scripts";
+
+pub const VERSUS_BOARD_GAP: f32 = 6.0;
+pub const ROYALE_SIDE_WIDTH: f32 = 18.0;
 
 #[rustfmt::skip]
 pub mod block_color {
@@ -54,6 +191,9 @@ pub mod block_color {
     pub const FG_BLUE:      [f32; 3] = [0.000, 0.133, 1.000];
     pub const FG_ORANGE:    [f32; 3] = [1.000, 0.133, 0.000];
     pub const FG_PURPLE:    [f32; 3] = [0.133, 0.000, 1.000];
+    pub const FG_GRAY:      [f32; 3] = [0.500, 0.500, 0.500];
+    pub const FG_PENDING:   [f32; 3] = [1.000, 0.250, 0.250];
+    pub const FG_CLEARING:  [f32; 3] = [0.850, 0.850, 0.850];
 }
 
 #[rustfmt::skip]
@@ -71,5 +211,6 @@ pub fn to_rgb(value: &BlockColor) -> [f32; 3] {
         BlockColor::Blue => block_color::FG_BLUE,
         BlockColor::Orange => block_color::FG_ORANGE,
         BlockColor::Purple => block_color::FG_PURPLE,
+        BlockColor::Gray => block_color::FG_GRAY,
     }
 }
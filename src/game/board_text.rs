@@ -0,0 +1,148 @@
+use crate::{consts, game};
+
+// a board as plain text, one line per row from the top down, where IOSZJLT are blocks of that
+// tetromino's color, G is garbage and . is empty
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    // counted from the top left, blank lines left out
+    Block { row: usize, col: usize, found: char },
+    Width { row: usize },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Block { row, col, found } => {
+                write!(f, "unknown block {} at row {}, column {}", found, row, col)
+            }
+            ParseError::Width { row } => write!(f, "row {} differs in width", row),
+        }
+    }
+}
+
+// the rows bottom row first, as the board keeps them; surrounding whitespace and blank lines are
+// left out so boards can be indented in fixtures
+pub fn parse(text: &str) -> Result<Vec<Vec<Option<consts::BlockColor>>>, ParseError> {
+    let rows = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let width = rows.first().map_or(0, |row| row.chars().count());
+
+    let mut blocks = rows
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            if line.chars().count() != width {
+                return Err(ParseError::Width { row });
+            }
+            line.chars()
+                .enumerate()
+                .map(|(col, found)| block_color(found).ok_or(ParseError::Block { row, col, found }))
+                .collect()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    blocks.reverse();
+    Ok(blocks)
+}
+
+// the rows from the highest one with a block down to the bottom, keeping at least the bottom row
+pub fn format(blocks: &[Vec<Option<consts::BlockColor>>]) -> String {
+    let height = blocks
+        .iter()
+        .rposition(|line| line.iter().any(Option::is_some))
+        .map_or(1, |top| top + 1);
+
+    blocks
+        .iter()
+        .take(height)
+        .rev()
+        .map(|line| {
+            let mut row = line.iter().map(block_letter).collect::<String>();
+            row.push('\n');
+            row
+        })
+        .collect()
+}
+
+pub fn block_color(block: char) -> Option<Option<consts::BlockColor>> {
+    match block {
+        '.' => Some(None),
+        'G' => Some(Some(consts::BlockColor::Gray)),
+        _ => game::puzzles::tetromino_index(block).map(|index| {
+            Some(
+                game::pieces::PieceSet::builtin()[0].pieces[index]
+                    .color
+                    .clone(),
+            )
+        }),
+    }
+}
+
+// the letter a block is written as, the inverse of block_color
+pub fn block_letter(block: &Option<consts::BlockColor>) -> char {
+    "IOSZJLTG"
+        .chars()
+        .find(|letter| block_color(*letter).as_ref() == Some(block))
+        .unwrap_or('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use consts::BlockColor;
+
+    #[test]
+    fn parses_rows_bottom_first() {
+        let blocks = parse(
+            "
+            ...T......
+            ..TTT....I
+            GGGGGGGG.I
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0][0], Some(BlockColor::Gray));
+        assert_eq!(blocks[0][8], None);
+        assert_eq!(blocks[1][9], Some(BlockColor::Cyan));
+        assert_eq!(blocks[2][3], Some(BlockColor::Purple));
+    }
+
+    #[test]
+    fn formats_from_the_highest_block_down() {
+        let mut blocks = vec![vec![None; 4]; 5];
+        blocks[0] = vec![
+            Some(BlockColor::Yellow),
+            Some(BlockColor::Yellow),
+            None,
+            Some(BlockColor::Gray),
+        ];
+        blocks[1][1] = Some(BlockColor::Red);
+
+        assert_eq!(format(&blocks), ".Z..\nOO.G\n");
+        assert_eq!(format(&vec![vec![None; 4]; 5]), "....\n");
+    }
+
+    #[test]
+    fn every_letter_survives_a_round_trip() {
+        let text = "IOSZ\nJLTG\n....\n";
+        assert_eq!(format(&parse(text).unwrap()), text);
+    }
+
+    #[test]
+    fn rejects_unknown_blocks_and_ragged_rows() {
+        assert_eq!(
+            parse("....\n..x."),
+            Err(ParseError::Block {
+                row: 1,
+                col: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(parse("....\n..."), Err(ParseError::Width { row: 1 }));
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::game::playing::{Action, GameSystem, Mino};
+use crate::{consts, game};
+
+const AGGREGATE_HEIGHT_WEIGHT: f32 = -0.51;
+const LINES_WEIGHT: f32 = 0.76;
+const HOLES_WEIGHT: f32 = -0.36;
+const BUMPINESS_WEIGHT: f32 = -0.18;
+
+pub struct Bot {
+    board: GameSystem,
+    think_time: Duration,
+    remaining_time: Duration,
+    topped_out: bool,
+}
+
+impl Bot {
+    pub fn new(seed: u64, think_time: Duration) -> Self {
+        Self {
+            board: GameSystem::with_bindings(game::playing::DEFAULT_BINDINGS, seed),
+            think_time,
+            remaining_time: Duration::ZERO,
+            topped_out: false,
+        }
+    }
+
+    pub fn step(&mut self, delta_time: Duration) {
+        if self.topped_out {
+            return;
+        }
+
+        let mut flow = game::GameSystemFlow::Default;
+
+        self.remaining_time += delta_time;
+        if self.think_time <= self.remaining_time && self.board.context().active_mino.is_some() {
+            for action in Self::best_actions(&self.board) {
+                self.board.action(action, &mut flow);
+            }
+            self.remaining_time = Duration::ZERO;
+        }
+
+        self.board.step(delta_time, &mut flow);
+
+        if let game::GameSystemFlow::To(_) = flow {
+            self.topped_out = true;
+        }
+    }
+
+    // tries every rotation and column on a copy of the board and keeps the best looking result
+    fn best_actions(board: &GameSystem) -> Vec<Action> {
+        let lines = *board.context().lines;
+        let width = board.dimensions().width;
+
+        let mut best = (f32::MIN, vec![Action::HardDrop]);
+        for rotation in 0..4 {
+            for shift in -width..=width {
+                let mut actions = vec![Action::RotateClockwise; rotation];
+                let direction = if shift < 0 {
+                    Action::MoveLeft
+                } else {
+                    Action::MoveRight
+                };
+                actions.extend(std::iter::repeat_n(
+                    direction,
+                    shift.unsigned_abs() as usize,
+                ));
+                actions.push(Action::HardDrop);
+
+                let mut next_board = board.clone();
+                let mut flow = game::GameSystemFlow::Default;
+                for action in actions.iter() {
+                    next_board.action(*action, &mut flow);
+                }
+
+                let cx = next_board.context();
+                let lines = *cx.lines - lines;
+                let value = if let game::GameSystemFlow::To(_) = flow {
+                    f32::MIN / 2.0
+                } else {
+                    // lines still being cleared are as good as gone
+                    let blocks = cx
+                        .blocks
+                        .iter()
+                        .enumerate()
+                        .filter(|(row, _)| !cx.clearing_lines.contains(row))
+                        .map(|(_, line)| line.clone())
+                        .collect::<Vec<_>>();
+                    Self::evaluate(&blocks, lines)
+                };
+
+                if best.0 < value {
+                    best = (value, actions);
+                }
+            }
+        }
+
+        best.1
+    }
+
+    fn evaluate(blocks: &[Vec<Option<consts::BlockColor>>], lines: i32) -> f32 {
+        let width = blocks.first().map_or(0, |line| line.len());
+        let heights = (0..width)
+            .map(|col| {
+                (0..blocks.len())
+                    .rev()
+                    .find(|row| blocks[*row][col].is_some())
+                    .map_or(0, |row| row + 1)
+            })
+            .collect::<Vec<_>>();
+
+        let aggregate_height = heights.iter().sum::<usize>();
+        let holes = heights
+            .iter()
+            .enumerate()
+            .map(|(col, height)| {
+                (0..*height)
+                    .filter(|row| blocks[*row][col].is_none())
+                    .count()
+            })
+            .sum::<usize>();
+        let bumpiness = heights
+            .windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]))
+            .sum::<usize>();
+
+        AGGREGATE_HEIGHT_WEIGHT * aggregate_height as f32
+            + LINES_WEIGHT * lines as f32
+            + HOLES_WEIGHT * holes as f32
+            + BUMPINESS_WEIGHT * bumpiness as f32
+    }
+}
+
+pub struct Report {
+    pub id: usize,
+    pub blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    pub active_mino: Option<Mino>,
+    pub pending_garbage: i32,
+    pub outgoing_garbage: i32,
+    pub topped_out: bool,
+}
+
+struct Command {
+    delta_time: Duration,
+    garbage: Vec<(usize, i32)>,
+}
+
+struct Worker {
+    sender: mpsc::Sender<Command>,
+    receiver: mpsc::Receiver<Vec<Report>>,
+    busy: bool,
+    delta_time: Duration,
+    garbage: Vec<(usize, i32)>,
+}
+
+// every worker thread owns a share of the bots, so the game loop never waits for them
+pub struct Pool {
+    workers: Vec<Worker>,
+    owners: Vec<usize>,
+}
+
+impl Pool {
+    pub fn new(bots: Vec<(usize, Bot)>, threads: usize) -> Self {
+        let threads = threads.clamp(1, bots.len().max(1));
+
+        let mut shares = (0..threads).map(|_| vec![]).collect::<Vec<_>>();
+        let mut owners = vec![0; bots.iter().map(|(id, _)| id + 1).max().unwrap_or(0)];
+        for (index, (id, bot)) in bots.into_iter().enumerate() {
+            owners[id] = index % threads;
+            shares[index % threads].push((id, bot));
+        }
+
+        let workers = shares
+            .into_iter()
+            .map(|mut share| {
+                let (sender, commands) = mpsc::channel::<Command>();
+                let (reports, receiver) = mpsc::channel();
+
+                std::thread::spawn(move || {
+                    while let Ok(command) = commands.recv() {
+                        for (id, lines) in command.garbage {
+                            if let Some((_, bot)) =
+                                share.iter_mut().find(|(bot_id, _)| *bot_id == id)
+                            {
+                                bot.board.receive_garbage(lines);
+                            }
+                        }
+
+                        let report = share
+                            .iter_mut()
+                            .map(|(id, bot)| {
+                                bot.step(command.delta_time);
+
+                                let outgoing_garbage = bot.board.take_outgoing_garbage();
+                                let cx = bot.board.context();
+                                Report {
+                                    id: *id,
+                                    blocks: cx.blocks.clone(),
+                                    active_mino: cx.active_mino.clone(),
+                                    pending_garbage: cx.pending_garbage.iter().sum(),
+                                    outgoing_garbage,
+                                    topped_out: bot.topped_out,
+                                }
+                            })
+                            .collect();
+
+                        if reports.send(report).is_err() {
+                            break;
+                        }
+                    }
+                });
+
+                Worker {
+                    sender,
+                    receiver,
+                    busy: false,
+                    delta_time: Duration::ZERO,
+                    garbage: vec![],
+                }
+            })
+            .collect();
+
+        Self { workers, owners }
+    }
+
+    pub fn send_garbage(&mut self, id: usize, lines: i32) {
+        let owner = self.owners[id];
+        self.workers[owner].garbage.push((id, lines));
+    }
+
+    // time keeps adding up for a busy worker and is handed over once it is idle again
+    pub fn step(&mut self, delta_time: Duration) {
+        for worker in self.workers.iter_mut() {
+            worker.delta_time += delta_time;
+
+            if !worker.busy {
+                let command = Command {
+                    delta_time: std::mem::take(&mut worker.delta_time),
+                    garbage: std::mem::take(&mut worker.garbage),
+                };
+                worker.busy = worker.sender.send(command).is_ok();
+            }
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<Report> {
+        let mut reports = vec![];
+
+        for worker in self.workers.iter_mut() {
+            if let Ok(report) = worker.receiver.try_recv() {
+                worker.busy = false;
+                reports.extend(report);
+            }
+        }
+
+        reports
+    }
+}
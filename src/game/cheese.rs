@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub elapsed: &'a Duration,
+    pub remaining_lines: i32,
+}
+
+// digs through a fixed number of garbage lines against the clock, keeping only a few of them
+// on the board at once
+pub struct GameSystem {
+    board: game::playing::GameSystem,
+    lines: i32,
+    placement: game::garbage::HolePlacement,
+    inserted_lines: i32,
+
+    last_update: Option<Duration>,
+    elapsed: Duration,
+}
+
+impl GameSystem {
+    pub fn new(
+        lines: i32,
+        placement: game::garbage::HolePlacement,
+        config: game::playing::Config,
+    ) -> Self {
+        let mut system = Self {
+            board: game::playing::GameSystem::with_config(
+                game::playing::DEFAULT_BINDINGS,
+                rand::random(),
+                config,
+            ),
+            lines: lines.max(1),
+            placement,
+            inserted_lines: 0,
+
+            last_update: None,
+            elapsed: Duration::ZERO,
+        };
+        system.refill(&mut game::GameSystemFlow::Default);
+        system
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if let Some(delta_time) = delta_time {
+            if !*self.board.context().paused {
+                self.elapsed += delta_time;
+            }
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    fn refill(&mut self, flow: &mut game::GameSystemFlow) {
+        // short boards keep fewer lines so that there is room left to play
+        let visible_lines =
+            consts::CHEESE_VISIBLE_LINES.min(self.board.dimensions().stack_height / 2);
+        let missing = visible_lines - self.board.garbage_lines();
+        let lines = missing.min(self.lines - self.inserted_lines);
+
+        if 0 < lines {
+            self.board.insert_garbage(lines, self.placement, flow);
+            self.inserted_lines += lines;
+        }
+    }
+
+    // a transition out of the board means it topped out
+    fn check_and_finish(
+        &mut self,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let mut board_flow = board_flow;
+        if let game::GameSystemFlow::Default = board_flow {
+            self.refill(&mut board_flow);
+        }
+
+        let is_topped_out = matches!(board_flow, game::GameSystemFlow::To(_));
+        let is_cleared = self.lines <= self.board.cleared_garbage();
+
+        if is_topped_out || is_cleared {
+            let state = game::GameSystem::CheeseEnd(game::cheese_end::GameSystem::new(
+                self.board.context().blocks.clone(),
+                self.board.config().clone(),
+                self.elapsed,
+                self.board.cleared_garbage().min(self.lines),
+                self.lines,
+                self.placement,
+                self.board.topped_out(),
+            ));
+            *flow = game::GameSystemFlow::To(state);
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            elapsed: &self.elapsed,
+            remaining_lines: self.lines - self.board.cleared_garbage().min(self.lines),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub level: &'a u32,
+}
+
+// the level select of the NES ruleset
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    level: u32,
+}
+
+impl GameSystem {
+    pub fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            level: 0,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Enter => {
+                            let state =
+                                game::GameSystem::Playing(game::playing::GameSystem::with_config(
+                                    game::playing::DEFAULT_BINDINGS,
+                                    rand::random(),
+                                    game::playing::Config::classic(self.level),
+                                ));
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::ArrowLeft => self.level = self.level.saturating_sub(1),
+                        KeyCode::ArrowRight => {
+                            self.level = (self.level + 1).min(consts::NES_MAX_START_LEVEL)
+                        }
+                        KeyCode::ArrowUp => self.level = self.level.saturating_sub(10),
+                        KeyCode::ArrowDown => {
+                            self.level = (self.level + 10).min(consts::NES_MAX_START_LEVEL)
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext { level: &self.level }
+    }
+}
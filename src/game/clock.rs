@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+// where the game reads the time from, passed to every update so tests can step time by hand and
+// the game can run slower or faster than the wall clock; network timeouts stay on the wall clock
+pub trait Clock {
+    // the time since the clock started
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+pub struct RealClock {
+    start: Instant,
+}
+
+impl RealClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// stands still until advanced, for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct ManualClock {
+    now: std::cell::Cell<Duration>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, delta_time: Duration) {
+        self.now.set(self.now.get() + delta_time);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+// runs another clock at a multiple of its speed, below 1 for slow motion and above for fast
+// forward
+pub struct ScaledClock<C: Clock> {
+    inner: C,
+    scale: f64,
+    // the times of both clocks when the scale last changed, so a new scale doesn't jump the time
+    inner_base: Duration,
+    base: Duration,
+}
+
+impl<C: Clock> ScaledClock<C> {
+    pub fn new(inner: C, scale: f64) -> Self {
+        let inner_base = inner.now();
+        Self {
+            inner,
+            scale: scale.max(0.0),
+            inner_base,
+            base: Duration::ZERO,
+        }
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f64) {
+        self.base = self.now();
+        self.inner_base = self.inner.now();
+        self.scale = scale.max(0.0);
+    }
+}
+
+impl<C: Clock> Clock for ScaledClock<C> {
+    fn now(&self) -> Duration {
+        let elapsed = self.inner.now().saturating_sub(self.inner_base);
+        self.base + elapsed.mul_f64(self.scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_moves_only_when_advanced() {
+        let clock = ManualClock::new();
+        assert_eq!(clock.now(), Duration::ZERO);
+
+        clock.advance(Duration::from_millis(250));
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.now(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn scaled_clock_keeps_its_time_across_scale_changes() {
+        let inner = ManualClock::new();
+        let mut clock = ScaledClock::new(&inner, 0.5);
+
+        inner.advance(Duration::from_secs(2));
+        assert_eq!(clock.now(), Duration::from_secs(1));
+
+        clock.set_scale(3.0);
+        assert_eq!(clock.now(), Duration::from_secs(1));
+
+        inner.advance(Duration::from_secs(1));
+        assert_eq!(clock.now(), Duration::from_secs(4));
+    }
+}
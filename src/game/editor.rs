@@ -0,0 +1,397 @@
+use std::collections::HashSet;
+
+use crate::game::puzzles::tetromino_index;
+use crate::{consts, game};
+
+// the colors to paint with, in the order of the palette
+pub const PALETTE: &[consts::BlockColor] = &[
+    consts::BlockColor::Cyan,
+    consts::BlockColor::Yellow,
+    consts::BlockColor::Green,
+    consts::BlockColor::Red,
+    consts::BlockColor::Blue,
+    consts::BlockColor::Orange,
+    consts::BlockColor::Purple,
+    consts::BlockColor::Gray,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    Paint,
+    FillRow,
+    ClearRow,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    Tool(Tool),
+    Hold,
+    Save,
+    Load,
+    Play,
+}
+
+// the buttons from the top down, with their labels
+pub const BUTTONS: &[(Button, &str)] = &[
+    (Button::Tool(Tool::Paint), "PAINT"),
+    (Button::Tool(Tool::FillRow), "FILL ROW"),
+    (Button::Tool(Tool::ClearRow), "CLEAR ROW"),
+    (Button::Hold, "HOLD"),
+    (Button::Save, "SAVE"),
+    (Button::Load, "LOAD"),
+    (Button::Play, "PLAY FROM HERE"),
+];
+
+// where the palette entry at the index sits, right of the board
+pub fn palette_cell(dimensions: &consts::Dimensions, index: usize) -> (i32, i32) {
+    (
+        dimensions.width + 1,
+        dimensions.visible_height - 2 - index as i32 * 2,
+    )
+}
+
+// where the button at the index starts, left of the board
+pub fn button_cell(dimensions: &consts::Dimensions, index: usize) -> (i32, i32) {
+    (
+        -1 - consts::EDITOR_BUTTON_WIDTH,
+        dimensions.visible_height - 2 - index as i32 * 2,
+    )
+}
+
+pub struct GameContext<'a> {
+    pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
+    pub color: usize,
+    pub tool: Tool,
+    pub queue: &'a str,
+    pub hold: Option<char>,
+    pub message: Option<&'a str>,
+}
+
+// the layout of a saved board, the rows from the top down in the plain text board format
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BoardFile {
+    board: Vec<String>,
+    queue: String,
+    hold: Option<char>,
+}
+
+// builds a board with the mouse, to be played from as it is
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    config: game::playing::Config,
+    // the visible rows, bottom row first
+    blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    color: usize,
+    tool: Tool,
+    // the pieces to deal and the piece in the hold, by their letters
+    queue: String,
+    hold: Option<char>,
+    // the cell under the cursor and the mouse button painting it, if any
+    cursor: (i32, i32),
+    painting: Option<winit::event::MouseButton>,
+    message: Option<String>,
+}
+
+impl GameSystem {
+    pub fn new(config: game::playing::Config) -> Self {
+        // the queue is written in tetromino letters
+        let config = game::playing::Config {
+            piece_set: game::pieces::PieceSet::builtin()[0].clone(),
+            ..config
+        };
+        let dimensions = config.dimensions;
+
+        Self {
+            pressed: HashSet::new(),
+
+            config,
+            blocks: vec![vec![None; dimensions.width as usize]; dimensions.visible_height as usize],
+            color: 0,
+            tool: Tool::Paint,
+            queue: String::new(),
+            hold: None,
+            cursor: (-1, -1),
+            painting: None,
+            message: None,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Enter => self.press(Button::Play, flow),
+                        KeyCode::KeyC if self.is_control_pressed() => {
+                            let page = game::fumen::Page::new(
+                                &self.blocks,
+                                None,
+                                &self.config.piece_set,
+                                "",
+                            );
+                            self.message = Some(
+                                match game::fumen::copy_to_clipboard(&game::fumen::encode(&[page]))
+                                {
+                                    Ok(()) => "COPIED FUMEN".to_string(),
+                                    Err(error) => error,
+                                },
+                            );
+                        }
+                        KeyCode::KeyV if self.is_control_pressed() => {
+                            let page = game::fumen::paste_from_clipboard().and_then(|source| {
+                                game::fumen::decode(&source).map_err(|error| error.to_string())
+                            });
+                            match page {
+                                Ok(pages) => self.set_page(&pages[0]),
+                                Err(error) => self.message = Some(error),
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            self.queue.pop();
+                        }
+                        _ => {
+                            let letter = match code {
+                                KeyCode::KeyI => Some('I'),
+                                KeyCode::KeyO => Some('O'),
+                                KeyCode::KeyS => Some('S'),
+                                KeyCode::KeyZ => Some('Z'),
+                                KeyCode::KeyJ => Some('J'),
+                                KeyCode::KeyL => Some('L'),
+                                KeyCode::KeyT => Some('T'),
+                                _ => None,
+                            };
+                            if let Some(letter) = letter {
+                                self.queue.push(letter);
+                            }
+                        }
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // a fumen page opened in the editor, its piece first in the queue and its comment shown
+    pub fn with_page(config: game::playing::Config, page: &game::fumen::Page) -> Self {
+        let mut system = Self::new(config);
+        system.set_page(page);
+        system
+    }
+
+    fn set_page(&mut self, page: &game::fumen::Page) {
+        self.set_blocks(page.blocks.clone());
+        self.queue = page
+            .operation
+            .map(|operation| operation.tetromino.letter().to_string())
+            .unwrap_or_default();
+        self.hold = None;
+        self.message = Some(page.comment.clone()).filter(|comment| !comment.is_empty());
+    }
+
+    // rows wider or narrower than the board are cut or padded, and the bottom rows kept
+    fn set_blocks(&mut self, mut blocks: Vec<Vec<Option<consts::BlockColor>>>) {
+        let width = self.config.dimensions.width as usize;
+        let height = self.config.dimensions.visible_height as usize;
+
+        blocks.truncate(height);
+        blocks.iter_mut().for_each(|line| line.resize(width, None));
+        blocks.resize(height, vec![None; width]);
+        self.blocks = blocks;
+    }
+
+    fn is_control_pressed(&self) -> bool {
+        use winit::keyboard::KeyCode;
+
+        self.pressed.contains(&KeyCode::ControlLeft)
+            || self.pressed.contains(&KeyCode::ControlRight)
+    }
+
+    pub fn mouse_input(&mut self, input: &game::MouseInput, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+
+        match *input {
+            game::MouseInput::Moved { x, y } => {
+                let cell = (
+                    (x + self.config.dimensions.width as f32 * 0.5).floor() as i32,
+                    (y + self.config.dimensions.visible_height as f32 * 0.5).floor() as i32,
+                );
+                if cell != self.cursor {
+                    self.cursor = cell;
+                    // dragging keeps painting cell by cell
+                    if let Some(button) = self.painting {
+                        self.apply_tool(button);
+                    }
+                }
+            }
+            game::MouseInput::Button {
+                button,
+                state: ElementState::Pressed,
+            } => self.click(button, flow),
+            game::MouseInput::Button {
+                button,
+                state: ElementState::Released,
+            } => {
+                if self.painting == Some(button) {
+                    self.painting = None;
+                }
+            }
+        }
+    }
+
+    fn click(&mut self, button: winit::event::MouseButton, flow: &mut game::GameSystemFlow) {
+        let dimensions = self.config.dimensions;
+        let (col, row) = self.cursor;
+
+        if (0..dimensions.width).contains(&col) && (0..dimensions.visible_height).contains(&row) {
+            self.painting = Some(button);
+            self.apply_tool(button);
+            return;
+        }
+
+        if button != winit::event::MouseButton::Left {
+            return;
+        }
+        if let Some(index) =
+            (0..PALETTE.len()).find(|index| palette_cell(&dimensions, *index) == (col, row))
+        {
+            self.color = index;
+        }
+        if let Some(index) = (0..BUTTONS.len()).find(|index| {
+            let (left, button_row) = button_cell(&dimensions, *index);
+            (left..left + consts::EDITOR_BUTTON_WIDTH).contains(&col) && button_row == row
+        }) {
+            self.press(BUTTONS[index].0, flow);
+        }
+    }
+
+    // the left button paints with the tool, the right one erases the cell whatever the tool
+    fn apply_tool(&mut self, button: winit::event::MouseButton) {
+        let (col, row) = self.cursor;
+        let Some(line) = usize::try_from(row)
+            .ok()
+            .and_then(|row| self.blocks.get_mut(row))
+        else {
+            return;
+        };
+        let Some(col) = usize::try_from(col).ok().filter(|col| *col < line.len()) else {
+            return;
+        };
+
+        let color = PALETTE[self.color].clone();
+        match (button, self.tool) {
+            (winit::event::MouseButton::Left, Tool::Paint) => line[col] = Some(color),
+            (winit::event::MouseButton::Left, Tool::FillRow) => line.fill(Some(color)),
+            (winit::event::MouseButton::Left, Tool::ClearRow) => line.fill(None),
+            (winit::event::MouseButton::Right, _) => line[col] = None,
+            _ => {}
+        }
+    }
+
+    fn press(&mut self, button: Button, flow: &mut game::GameSystemFlow) {
+        match button {
+            Button::Tool(tool) => self.tool = tool,
+            // the hold goes through every tetromino and back to empty
+            Button::Hold => {
+                const LETTERS: &str = "IOSZJLT";
+                self.hold = match self.hold {
+                    None => LETTERS.chars().next(),
+                    Some(letter) => LETTERS.chars().skip_while(|c| *c != letter).nth(1),
+                };
+            }
+            Button::Save => {
+                self.message = Some(match self.save() {
+                    Ok(()) => format!("SAVED TO {}", consts::EDITOR_BOARD_PATH),
+                    Err(error) => error,
+                });
+            }
+            Button::Load => {
+                self.message = Some(match self.load() {
+                    Ok(()) => format!("LOADED FROM {}", consts::EDITOR_BOARD_PATH),
+                    Err(error) => error,
+                });
+            }
+            Button::Play => {
+                let mut board = game::playing::GameSystem::with_config(
+                    game::playing::DEFAULT_BINDINGS,
+                    rand::random(),
+                    self.config.clone(),
+                );
+                board.set_blocks(self.blocks.clone());
+                board.set_sequence(self.queue.chars().filter_map(tetromino_index));
+                board.set_held_piece(self.hold.and_then(tetromino_index));
+
+                *flow = game::GameSystemFlow::To(game::GameSystem::Playing(board));
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let file = BoardFile {
+            board: game::board_text::format(&self.blocks)
+                .lines()
+                .map(String::from)
+                .collect(),
+            queue: self.queue.clone(),
+            hold: self.hold,
+        };
+
+        toml::to_string(&file)
+            .map_err(|error| error.to_string())
+            .and_then(|source| {
+                std::fs::write(consts::EDITOR_BOARD_PATH, source).map_err(|error| error.to_string())
+            })
+            .map_err(|error| format!("FAILED TO SAVE: {}", error))
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        let file = std::fs::read_to_string(consts::EDITOR_BOARD_PATH)
+            .map_err(|error| error.to_string())
+            .and_then(|source| {
+                toml::from_str::<BoardFile>(&source).map_err(|error| error.to_string())
+            })
+            .map_err(|error| format!("FAILED TO LOAD: {}", error))?;
+
+        let blocks = game::board_text::parse(&file.board.join("\n"))
+            .map_err(|error| format!("FAILED TO LOAD: {}", error))?;
+
+        self.set_blocks(blocks);
+        self.queue = file
+            .queue
+            .chars()
+            .filter(|letter| tetromino_index(*letter).is_some())
+            .collect();
+        self.hold = file
+            .hold
+            .filter(|letter| tetromino_index(*letter).is_some());
+        Ok(())
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            blocks: &self.blocks,
+            dimensions: &self.config.dimensions,
+            color: self.color,
+            tool: self.tool,
+            queue: &self.queue,
+            hold: self.hold,
+            message: self.message.as_deref(),
+        }
+    }
+}
@@ -4,23 +4,34 @@ use crate::{consts, game};
 
 pub struct GameContext<'a> {
     pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
     pub score: &'a i32,
+    pub top_out: &'a game::playing::TopOut,
 }
 
 pub struct GameSystem {
     pressed: HashSet<winit::keyboard::KeyCode>,
 
     blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    config: game::playing::Config,
     score: i32,
+    top_out: game::playing::TopOut,
 }
 
 impl GameSystem {
-    pub fn new(blocks: Vec<Vec<Option<consts::BlockColor>>>, score: i32) -> Self {
+    pub fn new(
+        blocks: Vec<Vec<Option<consts::BlockColor>>>,
+        config: game::playing::Config,
+        score: i32,
+        top_out: game::playing::TopOut,
+    ) -> Self {
         Self {
             pressed: HashSet::new(),
 
             blocks,
+            config,
             score,
+            top_out,
         }
     }
 
@@ -31,9 +42,21 @@ impl GameSystem {
         if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
             match input.state {
                 ElementState::Pressed if !self.pressed.contains(&code) => {
-                    if code == KeyCode::Enter {
-                        let state = game::GameSystem::Playing(game::playing::GameSystem::new());
-                        *flow = game::GameSystemFlow::To(state);
+                    match code {
+                        KeyCode::Enter => {
+                            let state =
+                                game::GameSystem::Playing(game::playing::GameSystem::with_config(
+                                    game::playing::DEFAULT_BINDINGS,
+                                    rand::random(),
+                                    self.config.clone(),
+                                ));
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
                     }
                     self.pressed.insert(code);
                 }
@@ -45,14 +68,16 @@ impl GameSystem {
         }
     }
 
-    pub fn update(&mut self, _flow: &mut game::GameSystemFlow) {
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
         // nothing
     }
 
     pub fn context(&'_ self) -> GameContext<'_> {
         GameContext {
             blocks: &self.blocks,
+            dimensions: &self.config.dimensions,
             score: &self.score,
+            top_out: &self.top_out,
         }
     }
 }
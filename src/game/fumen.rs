@@ -0,0 +1,752 @@
+use crate::game::board_text::{block_color, block_letter};
+use crate::game::rotation::Tetromino;
+use crate::{consts, game};
+
+// fumen (https://fumen.zui.jp) as written by its v115 encoding
+const PREFIX: &str = "v115@";
+const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8] =
+    b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const MAX_COMMENT_LENGTH: usize = 4095;
+
+pub const FIELD_WIDTH: usize = 10;
+pub const FIELD_HEIGHT: usize = 23;
+// the field rows from the top down, then the garbage row waiting under them
+const FIELD_BLOCKS: usize = (FIELD_HEIGHT + 1) * FIELD_WIDTH;
+
+// fumen numbers its blocks from 1 in this order, 0 being empty
+const LETTERS: &str = "ILOZTJSG";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rotation {
+    Spawn,
+    Right,
+    Reverse,
+    Left,
+}
+
+// a piece where fumen puts it, around its rotation center
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Operation {
+    pub tetromino: Tetromino,
+    pub rotation: Rotation,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Page {
+    // FIELD_HEIGHT rows of FIELD_WIDTH blocks, bottom row first
+    pub blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    pub operation: Option<Operation>,
+    pub comment: String,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Prefix,
+    Character(char),
+    Truncated,
+    Invalid(&'static str),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Prefix => write!(f, "not a v115 fumen"),
+            DecodeError::Character(c) => write!(f, "unexpected character in fumen: {}", c),
+            DecodeError::Truncated => write!(f, "fumen ends too early"),
+            DecodeError::Invalid(reason) => write!(f, "invalid fumen: {}", reason),
+        }
+    }
+}
+
+impl Operation {
+    // the cells of a tetromino in its spawn rotation, around the center
+    fn shape(tetromino: Tetromino) -> [(i32, i32); 4] {
+        match tetromino {
+            Tetromino::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            Tetromino::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+            Tetromino::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Tetromino::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+            Tetromino::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            Tetromino::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            Tetromino::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        }
+    }
+
+    pub fn cells(&self) -> [(i32, i32); 4] {
+        Self::shape(self.tetromino).map(|(x, y)| {
+            let (x, y) = match self.rotation {
+                Rotation::Spawn => (x, y),
+                Rotation::Right => (y, -x),
+                Rotation::Reverse => (-x, -y),
+                Rotation::Left => (-y, x),
+            };
+            (self.x + x, self.y + y)
+        })
+    }
+
+    // the operation covering these cells, whichever way the rotation system turned the piece
+    pub fn from_cells(tetromino: Tetromino, cells: &[(i32, i32)]) -> Option<Operation> {
+        let mut cells = cells.to_vec();
+        cells.sort();
+
+        [
+            Rotation::Spawn,
+            Rotation::Right,
+            Rotation::Reverse,
+            Rotation::Left,
+        ]
+        .into_iter()
+        .find_map(|rotation| {
+            let origin = Operation {
+                tetromino,
+                rotation,
+                x: 0,
+                y: 0,
+            };
+            let mut shape = origin.cells().to_vec();
+            shape.sort();
+
+            let (x, y) = (cells.first()?.0 - shape[0].0, cells.first()?.1 - shape[0].1);
+            let operation = Operation { x, y, ..origin };
+            let mut placed = operation.cells().to_vec();
+            placed.sort();
+            (placed == cells).then_some(operation)
+        })
+    }
+}
+
+impl Page {
+    // a page of a board of any size, cut or padded to the fumen field, with the mino on it
+    pub fn new(
+        blocks: &[Vec<Option<consts::BlockColor>>],
+        mino: Option<&game::playing::Mino>,
+        piece_set: &game::pieces::PieceSet,
+        comment: &str,
+    ) -> Self {
+        let mut blocks = blocks
+            .iter()
+            .take(FIELD_HEIGHT)
+            .map(|line| {
+                let mut line = line.iter().take(FIELD_WIDTH).cloned().collect::<Vec<_>>();
+                line.resize(FIELD_WIDTH, None);
+                line
+            })
+            .collect::<Vec<_>>();
+        blocks.resize(FIELD_HEIGHT, vec![None; FIELD_WIDTH]);
+
+        let operation = mino.and_then(|mino| {
+            let tetromino = Tetromino::of(&piece_set.pieces[mino.piece])?;
+            let cells = mino
+                .blocks
+                .iter()
+                .map(|(x, y)| (mino.x + x, mino.y + y))
+                .collect::<Vec<_>>();
+            Operation::from_cells(tetromino, &cells)
+        });
+
+        Self {
+            blocks,
+            operation,
+            comment: comment.to_string(),
+        }
+    }
+
+    fn field(&self) -> Vec<u8> {
+        let mut field = vec![0; FIELD_BLOCKS];
+        for (y, line) in self.blocks.iter().enumerate() {
+            for (x, block) in line.iter().enumerate() {
+                field[index(x as i32, y as i32)] = block_number(block);
+            }
+        }
+        field
+    }
+}
+
+// every placement of a game as a page, then the board as it is now with the falling mino
+pub fn game_pages(board: &game::playing::GameSystem) -> Vec<Page> {
+    let piece_set = &board.config().piece_set;
+    let cx = board.context();
+
+    board
+        .replay()
+        .iter()
+        .map(|(blocks, mino)| Page::new(blocks, Some(mino), piece_set, ""))
+        .chain(std::iter::once(Page::new(
+            cx.blocks,
+            cx.active_mino.as_ref(),
+            piece_set,
+            "",
+        )))
+        .collect()
+}
+
+pub fn encode(pages: &[Page]) -> String {
+    let mut values = vec![];
+    let mut prev_field = vec![0; FIELD_BLOCKS];
+    let mut prev_comment = String::new();
+    // where the count of pages repeating the last unchanged field is written
+    let mut repeat_index = None::<usize>;
+
+    for (page_index, page) in pages.iter().enumerate() {
+        let field = page.field();
+
+        // every block is written as its difference to the block before, in runs of the same
+        let diffs = field
+            .iter()
+            .zip(prev_field.iter())
+            .map(|(block, prev)| (*block as i32 - *prev as i32 + 8) as usize)
+            .collect::<Vec<_>>();
+        let changed = diffs.iter().any(|diff| *diff != 8);
+        match repeat_index {
+            Some(repeat) if !changed && values[repeat] < TABLE.len() - 1 => values[repeat] += 1,
+            _ => {
+                let mut start = 0;
+                while start < diffs.len() {
+                    let length = diffs[start..]
+                        .iter()
+                        .take_while(|diff| **diff == diffs[start])
+                        .count();
+                    push(&mut values, diffs[start] * FIELD_BLOCKS + length - 1, 2);
+                    start += length;
+                }
+
+                repeat_index = if changed {
+                    None
+                } else {
+                    values.push(0);
+                    Some(values.len() - 1)
+                };
+            }
+        }
+
+        let comment = page.comment != prev_comment;
+        let (piece, rotation, position) = match page.operation {
+            Some(operation) => encode_operation(&operation),
+            None => (0, 0, 0),
+        };
+        let mut action = 0;
+        for flag in [false, comment, page_index == 0, false, false] {
+            action = action * 2 + flag as usize;
+        }
+        action = ((action * FIELD_BLOCKS + position) * 4 + rotation) * 8 + piece;
+        push(&mut values, action, 3);
+
+        if comment {
+            let escaped = escape(&page.comment)
+                .chars()
+                .take(MAX_COMMENT_LENGTH)
+                .collect::<Vec<_>>();
+            push(&mut values, escaped.len(), 2);
+            for chunk in escaped.chunks(4) {
+                let value = chunk.iter().rev().fold(0, |value, c| {
+                    let index = COMMENT_TABLE.iter().position(|t| *t as char == *c);
+                    value * (COMMENT_TABLE.len() + 1) + index.unwrap_or(0)
+                });
+                push(&mut values, value, 5);
+            }
+            prev_comment = page.comment.clone();
+        }
+
+        prev_field = field;
+        lock(&mut prev_field, page.operation.as_ref(), false, false);
+    }
+
+    // long fumens are broken up with question marks, first after 42 characters then every 47
+    let data = values
+        .iter()
+        .map(|value| TABLE[*value] as char)
+        .collect::<Vec<_>>();
+    let mut chunks = vec![data.iter().take(42).collect::<String>()];
+    chunks.extend(data[data.len().min(42)..].chunks(47).map(String::from_iter));
+    format!("{}{}", PREFIX, chunks.join("?"))
+}
+
+pub fn decode(source: &str) -> Result<Vec<Page>, DecodeError> {
+    let start = source.find(PREFIX).ok_or(DecodeError::Prefix)?;
+    let mut reader = Reader {
+        values: source[start + PREFIX.len()..]
+            .trim()
+            .chars()
+            .filter(|c| *c != '?')
+            .map(|c| {
+                TABLE
+                    .iter()
+                    .position(|t| *t as char == c)
+                    .ok_or(DecodeError::Character(c))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+
+    let mut pages = vec![];
+    let mut prev_field = vec![0; FIELD_BLOCKS];
+    let mut comment = String::new();
+    let mut repeat = 0;
+
+    while !reader.values.is_empty() {
+        let mut field = prev_field.clone();
+        if 0 < repeat {
+            repeat -= 1;
+        } else {
+            let mut block = 0;
+            let mut changed = false;
+            while block < FIELD_BLOCKS {
+                let value = reader.poll(2)?;
+                let (diff, length) = (value / FIELD_BLOCKS, value % FIELD_BLOCKS + 1);
+                if FIELD_BLOCKS < block + length {
+                    return Err(DecodeError::Invalid("field overflows"));
+                }
+                changed |= diff != 8;
+                for item in field.iter_mut().skip(block).take(length) {
+                    let next = *item as i32 + diff as i32 - 8;
+                    *item = u8::try_from(next)
+                        .ok()
+                        .filter(|next| *next as usize <= LETTERS.len())
+                        .ok_or(DecodeError::Invalid("unknown block"))?;
+                }
+                block += length;
+            }
+            if !changed {
+                repeat = reader.poll(1)?;
+            }
+        }
+
+        let mut action = reader.poll(3)?;
+        let piece = action % 8;
+        action /= 8;
+        let rotation = action % 4;
+        action /= 4;
+        let position = action % FIELD_BLOCKS;
+        action /= FIELD_BLOCKS;
+        let mut flags = [false; 5];
+        for flag in flags.iter_mut().rev() {
+            *flag = action % 2 == 1;
+            action /= 2;
+        }
+        let [not_lock, has_comment, _colorize, mirror, rise] = flags;
+
+        let operation = (piece != 0).then(|| decode_operation(piece, rotation, position));
+
+        if has_comment {
+            let length = reader.poll(2)?;
+            let mut escaped = String::new();
+            for _ in 0..length.div_ceil(4) {
+                let mut value = reader.poll(5)?;
+                for _ in 0..4 {
+                    let c = COMMENT_TABLE
+                        .get(value % (COMMENT_TABLE.len() + 1))
+                        .ok_or(DecodeError::Invalid("unknown comment character"))?;
+                    escaped.push(*c as char);
+                    value /= COMMENT_TABLE.len() + 1;
+                }
+            }
+            escaped.truncate(length);
+            comment = unescape(&escaped);
+        }
+
+        pages.push(Page {
+            blocks: (0..FIELD_HEIGHT as i32)
+                .map(|y| {
+                    (0..FIELD_WIDTH as i32)
+                        .map(|x| number_block(field[index(x, y)]))
+                        .collect()
+                })
+                .collect(),
+            operation,
+            comment: comment.clone(),
+        });
+
+        if !not_lock {
+            lock(&mut field, operation.as_ref(), rise, mirror);
+        }
+        prev_field = field;
+    }
+
+    if pages.is_empty() {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(pages)
+}
+
+struct Reader {
+    values: std::collections::VecDeque<usize>,
+}
+
+impl Reader {
+    // little-endian digits of the table
+    fn poll(&mut self, count: usize) -> Result<usize, DecodeError> {
+        let mut value = 0;
+        for scale in (0..count).map(|digit| TABLE.len().pow(digit as u32)) {
+            value += self.values.pop_front().ok_or(DecodeError::Truncated)? * scale;
+        }
+        Ok(value)
+    }
+}
+
+thread_local! {
+    // kept open, as some platforms drop what was copied when the clipboard closes
+    static CLIPBOARD: std::cell::RefCell<Option<arboard::Clipboard>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+fn with_clipboard<T>(
+    f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+) -> Result<T, arboard::Error> {
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new()?);
+        }
+        f(clipboard.as_mut().unwrap())
+    })
+}
+
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    with_clipboard(|clipboard| clipboard.set_text(text))
+        .map_err(|error| format!("failed to copy to the clipboard: {}", error))
+}
+
+pub fn paste_from_clipboard() -> Result<String, String> {
+    with_clipboard(|clipboard| clipboard.get_text())
+        .map_err(|error| format!("failed to paste from the clipboard: {}", error))
+}
+
+// the index of a block in the field, y counting up from the bottom row and -1 being garbage
+fn index(x: i32, y: i32) -> usize {
+    ((FIELD_HEIGHT as i32 - 1 - y) * FIELD_WIDTH as i32 + x) as usize
+}
+
+fn block_number(block: &Option<consts::BlockColor>) -> u8 {
+    LETTERS
+        .chars()
+        .position(|letter| letter == block_letter(block))
+        .map_or(0, |position| position as u8 + 1)
+}
+
+fn number_block(number: u8) -> Option<consts::BlockColor> {
+    LETTERS
+        .chars()
+        .nth((number as usize).checked_sub(1)?)
+        .and_then(block_color)
+        .flatten()
+}
+
+fn tetromino_number(tetromino: Tetromino) -> usize {
+    match tetromino {
+        Tetromino::I => 1,
+        Tetromino::L => 2,
+        Tetromino::O => 3,
+        Tetromino::Z => 4,
+        Tetromino::T => 5,
+        Tetromino::J => 6,
+        Tetromino::S => 7,
+    }
+}
+
+// fumen keeps some pieces a block away from the center it turns them around
+fn position_shift(tetromino: Tetromino, rotation: Rotation) -> (i32, i32) {
+    match (tetromino, rotation) {
+        (Tetromino::O, Rotation::Left) => (1, -1),
+        (Tetromino::O, Rotation::Reverse) => (1, 0),
+        (Tetromino::O, Rotation::Spawn) => (0, -1),
+        (Tetromino::I, Rotation::Reverse) => (1, 0),
+        (Tetromino::I, Rotation::Left) => (0, -1),
+        (Tetromino::S, Rotation::Spawn) => (0, -1),
+        (Tetromino::S, Rotation::Right) => (-1, 0),
+        (Tetromino::Z, Rotation::Spawn) => (0, -1),
+        (Tetromino::Z, Rotation::Left) => (1, 0),
+        _ => (0, 0),
+    }
+}
+
+fn encode_operation(operation: &Operation) -> (usize, usize, usize) {
+    let rotation = match operation.rotation {
+        Rotation::Reverse => 0,
+        Rotation::Right => 1,
+        Rotation::Spawn => 2,
+        Rotation::Left => 3,
+    };
+    let (shift_x, shift_y) = position_shift(operation.tetromino, operation.rotation);
+    let position = index(operation.x + shift_x, operation.y + shift_y);
+    (tetromino_number(operation.tetromino), rotation, position)
+}
+
+fn decode_operation(piece: usize, rotation: usize, position: usize) -> Operation {
+    let tetromino = [
+        Tetromino::I,
+        Tetromino::L,
+        Tetromino::O,
+        Tetromino::Z,
+        Tetromino::T,
+        Tetromino::J,
+        Tetromino::S,
+    ][piece - 1];
+    let rotation = [
+        Rotation::Reverse,
+        Rotation::Right,
+        Rotation::Spawn,
+        Rotation::Left,
+    ][rotation];
+    let (shift_x, shift_y) = position_shift(tetromino, rotation);
+
+    Operation {
+        tetromino,
+        rotation,
+        x: (position % FIELD_WIDTH) as i32 - shift_x,
+        y: FIELD_HEIGHT as i32 - 1 - (position / FIELD_WIDTH) as i32 - shift_y,
+    }
+}
+
+// what the next page starts from: the piece placed, full lines cleared, garbage risen and the
+// field mirrored as the page asks
+fn lock(field: &mut Vec<u8>, operation: Option<&Operation>, rise: bool, mirror: bool) {
+    if let Some(operation) = operation {
+        for (x, y) in operation.cells() {
+            if (0..FIELD_WIDTH as i32).contains(&x) && (0..FIELD_HEIGHT as i32).contains(&y) {
+                field[index(x, y)] = tetromino_number(operation.tetromino) as u8;
+            }
+        }
+    }
+
+    let (rows, garbage) = field.split_at(FIELD_HEIGHT * FIELD_WIDTH);
+    let mut rows = rows
+        .chunks(FIELD_WIDTH)
+        .filter(|row| row.contains(&0))
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+    let mut garbage = garbage.to_vec();
+    while rows.len() < FIELD_HEIGHT {
+        rows.insert(0, vec![0; FIELD_WIDTH]);
+    }
+    if rise {
+        rows.push(std::mem::replace(&mut garbage, vec![0; FIELD_WIDTH]));
+        rows.remove(0);
+    }
+    if mirror {
+        rows.iter_mut().for_each(|row| row.reverse());
+    }
+
+    *field = rows.concat();
+    field.extend(garbage);
+}
+
+// little-endian digits of the table
+fn push(values: &mut Vec<usize>, mut value: usize, count: usize) {
+    for _ in 0..count {
+        values.push(value % TABLE.len());
+        value /= TABLE.len();
+    }
+}
+
+// comments are escaped as javascript's escape() does before they are packed
+fn escape(text: &str) -> String {
+    text.encode_utf16()
+        .map(|unit| match char::from_u32(unit as u32) {
+            Some(c) if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => c.to_string(),
+            _ if unit < 256 => format!("%{:02X}", unit),
+            _ => format!("%u{:04X}", unit),
+        })
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    let mut units = vec![];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let unit = match c {
+            '%' if rest.starts_with("%u") => rest
+                .get(2..6)
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                .map(|unit| (unit, 6)),
+            '%' => rest
+                .get(1..3)
+                .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                .map(|unit| (unit, 3)),
+            _ => None,
+        };
+        let (unit, length) = unit.unwrap_or((c as u16, c.len_utf8()));
+        units.push(unit);
+        rest = &rest[length..];
+    }
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::playing;
+    use std::time::Duration;
+
+    fn empty_page() -> Page {
+        Page {
+            blocks: vec![vec![None; FIELD_WIDTH]; FIELD_HEIGHT],
+            operation: None,
+            comment: String::new(),
+        }
+    }
+
+    // a page with the board given in the plain text board format
+    fn board_page(board: &str) -> Page {
+        let mut blocks = game::board_text::parse(board).unwrap();
+        blocks.resize(FIELD_HEIGHT, vec![None; FIELD_WIDTH]);
+        Page {
+            blocks,
+            ..empty_page()
+        }
+    }
+
+    fn garbage_page() -> Page {
+        board_page("GGGGGGGGG.")
+    }
+
+    fn t_page() -> Page {
+        Page {
+            operation: Some(Operation {
+                tetromino: Tetromino::T,
+                rotation: Rotation::Spawn,
+                x: 4,
+                y: 0,
+            }),
+            ..empty_page()
+        }
+    }
+
+    fn comment_page() -> Page {
+        Page {
+            comment: "hi".to_string(),
+            ..empty_page()
+        }
+    }
+
+    fn golden() -> Vec<(&'static str, Vec<Page>)> {
+        vec![
+            ("v115@vhAAgH", vec![empty_page()]),
+            ("v115@vhAVQJ", vec![t_page()]),
+            ("v115@bhI8KeAgH", vec![garbage_page()]),
+            ("v115@vhAAgWCAouBAA", vec![comment_page()]),
+            ("v115@vhBAgHAAA", vec![empty_page(), empty_page()]),
+        ]
+    }
+
+    #[test]
+    fn encodes_golden_strings() {
+        for (fumen, pages) in golden() {
+            assert_eq!(encode(&pages), fumen);
+        }
+    }
+
+    #[test]
+    fn decodes_golden_strings() {
+        for (fumen, pages) in golden() {
+            assert_eq!(decode(fumen).unwrap(), pages, "{}", fumen);
+        }
+    }
+
+    #[test]
+    fn placed_pieces_carry_over_to_the_next_page() {
+        let placed = board_page(
+            "
+            ....T.....
+            ...TTT....
+            ",
+        );
+
+        let fumen = encode(&[t_page(), placed.clone()]);
+        assert_eq!(fumen, "v115@vhBVQJAAA");
+        assert_eq!(decode(&fumen).unwrap(), vec![t_page(), placed]);
+    }
+
+    #[test]
+    fn every_rotation_survives_a_round_trip() {
+        let tetrominoes = [
+            Tetromino::I,
+            Tetromino::O,
+            Tetromino::T,
+            Tetromino::S,
+            Tetromino::Z,
+            Tetromino::J,
+            Tetromino::L,
+        ];
+        let rotations = [
+            Rotation::Spawn,
+            Rotation::Right,
+            Rotation::Reverse,
+            Rotation::Left,
+        ];
+
+        for tetromino in tetrominoes {
+            for rotation in rotations {
+                let operation = Operation {
+                    tetromino,
+                    rotation,
+                    x: 4,
+                    y: 10,
+                };
+                let page = Page {
+                    operation: Some(operation),
+                    ..empty_page()
+                };
+                let decoded = decode(&encode(&[page])).unwrap();
+                let cells = decoded[0].operation.unwrap().cells();
+                assert_eq!(cells, operation.cells(), "{:?} {:?}", tetromino, rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn long_fumens_are_broken_up_and_read_back() {
+        let comment = "T-spin triple! テトリス 100%".to_string();
+        let pages = (0..20)
+            .map(|index| Page {
+                comment: format!("{} {}", comment, index),
+                ..garbage_page()
+            })
+            .collect::<Vec<_>>();
+
+        let fumen = encode(&pages);
+        assert!(fumen.contains('?'));
+        assert_eq!(decode(&fumen).unwrap(), pages);
+    }
+
+    #[test]
+    fn a_replay_survives_a_round_trip() {
+        let config = playing::Config {
+            replay: true,
+            entry_delay: Duration::ZERO,
+            line_clear_delay: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut board = playing::GameSystem::with_config(playing::DEFAULT_BINDINGS, 7, config);
+        let mut flow = game::GameSystemFlow::Default;
+        let actions = [
+            playing::Action::MoveLeft,
+            playing::Action::RotateClockwise,
+            playing::Action::MoveRight,
+            playing::Action::RotateCounterClockwise,
+        ];
+        for index in 0..12 {
+            if board.context().active_mino.is_none() {
+                board.step(consts::UPDATE_INTERVAL * 2, &mut flow);
+            }
+            board.action(actions[index % actions.len()], &mut flow);
+            board.action(playing::Action::HardDrop, &mut flow);
+        }
+        board.step(Duration::from_millis(1), &mut flow);
+
+        let pages = game_pages(&board);
+        assert_eq!(board.replay().len(), 12);
+        assert_eq!(pages.len(), 13);
+        assert_eq!(decode(&encode(&pages)).unwrap(), pages);
+    }
+
+    #[test]
+    fn rejects_broken_fumens() {
+        assert!(matches!(decode("hello"), Err(DecodeError::Prefix)));
+        assert!(matches!(
+            decode("v115@vh!"),
+            Err(DecodeError::Character('!'))
+        ));
+        assert!(matches!(decode("v115@vhA"), Err(DecodeError::Truncated)));
+    }
+}
@@ -0,0 +1,105 @@
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestError, TestRunner};
+
+use crate::game::harness::Replay;
+
+// random scripts played through the harness, which checks the game invariants after every step;
+// a failing script is shrunk and saved to the replay directory, where it is played by every later
+// run until it passes
+const REPLAY_DIR: &str = "fuzz/replays";
+
+fn replay_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(REPLAY_DIR)
+}
+
+// a few rows of garbage, each with at least one hole so the board starts without full rows
+fn board() -> impl Strategy<Value = Vec<String>> {
+    prop::collection::vec(
+        (prop::collection::vec(any::<bool>(), 10), 0..10usize),
+        0..16,
+    )
+    .prop_map(|rows| {
+        rows.into_iter()
+            .map(|(blocks, hole)| {
+                blocks
+                    .iter()
+                    .enumerate()
+                    .map(|(col, block)| if *block && col != hole { 'G' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    })
+}
+
+fn script() -> impl Strategy<Value = String> {
+    let word = prop_oneof![
+        Just("left"),
+        Just("right"),
+        Just("down"),
+        Just("drop"),
+        Just("cw"),
+        Just("ccw"),
+        Just("flip"),
+        Just("hold"),
+        Just("tick"),
+        Just("tick*8"),
+    ];
+    prop::collection::vec(word, 0..400).prop_map(|words| words.join(" "))
+}
+
+fn replay() -> impl Strategy<Value = Replay> {
+    (any::<u64>(), board(), script()).prop_map(|(seed, board, script)| Replay {
+        seed,
+        board,
+        pieces: String::new(),
+        script,
+    })
+}
+
+#[test]
+fn random_scripts_keep_the_invariants() {
+    // proptest's own persistence would only keep the seed, the replay file keeps the script
+    let config = Config {
+        failure_persistence: None,
+        ..Config::default()
+    };
+
+    match TestRunner::new(config).run(&replay(), |replay| {
+        replay.play();
+        Ok(())
+    }) {
+        Ok(()) => {}
+        Err(TestError::Fail(reason, replay)) => {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            (replay.seed, &replay.board, &replay.script).hash(&mut hasher);
+            let path = replay_dir().join(format!("{:016x}.toml", hasher.finish()));
+
+            std::fs::create_dir_all(replay_dir()).unwrap();
+            replay.save(&path).unwrap();
+            panic!("{}, shrunk to {}", reason, path.display());
+        }
+        Err(error) => panic!("{}", error),
+    }
+}
+
+#[test]
+fn saved_replays_keep_the_invariants() {
+    let mut paths = std::fs::read_dir(replay_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        Replay::load(&path)
+            .unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+            .play();
+    }
+}
@@ -0,0 +1,61 @@
+use rand::prelude::*;
+use rand::rngs::Xoshiro256PlusPlus;
+
+use crate::consts;
+
+// where the hole of each inserted garbage row goes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HolePlacement {
+    // every row of one insertion shares the same hole
+    Clean,
+    // every row gets a hole of its own
+    Random,
+    // each row moves the hole away from the row below with the given percentage
+    Messiness(u32),
+}
+
+// pushes garbage rows in from the bottom and shifts the board up by as many rows, returning
+// whether any block was pushed out of the top of the board
+pub fn insert(
+    blocks: &mut Vec<Vec<Option<consts::BlockColor>>>,
+    lines: i32,
+    placement: HolePlacement,
+    rng: &mut Xoshiro256PlusPlus,
+) -> bool {
+    let width = blocks.first().map_or(0, |line| line.len());
+    if width == 0 {
+        return false;
+    }
+
+    let mut overflowed = false;
+    let mut hole = rng.random_range(0..width);
+
+    for index in 0..lines {
+        if 0 < index && 1 < width {
+            match placement {
+                HolePlacement::Clean => {}
+                HolePlacement::Random => hole = rng.random_range(0..width),
+                HolePlacement::Messiness(percent) => {
+                    if rng.random_bool(percent.min(100) as f64 / 100.0) {
+                        hole = (hole + rng.random_range(1..width)) % width;
+                    }
+                }
+            }
+        }
+
+        let mut line = vec![Some(consts::BlockColor::Gray); width];
+        line[hole] = None;
+
+        if let Some(top) = blocks.pop() {
+            overflowed |= top.iter().any(|block| block.is_some());
+        }
+        blocks.insert(0, line);
+    }
+
+    overflowed
+}
+
+pub fn is_garbage_line(line: &[Option<consts::BlockColor>]) -> bool {
+    line.iter()
+        .any(|block| matches!(block, Some(consts::BlockColor::Gray)))
+}
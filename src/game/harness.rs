@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use crate::game::clock::ManualClock;
+use crate::game::playing::{Action, Config, GameSystem, TopOut};
+use crate::{consts, game};
+
+// plays a game from a plain text board, a fixed seed and a script, so the rules can be pinned down
+// in tests without a window, on a clock that only moves when the script says so
+//
+// a script is a list of words split by whitespace, each one optionally repeated as word*count:
+// left, right, down, drop, cw, ccw, flip and hold are the actions, tick steps the game by one
+// network tick and next steps until a new mino is falling or the game is over, after which the
+// rest of the script is left out as the game would have moved on to the end screen
+//
+// the game invariants are checked after every step and action, so any script doubles as a test
+// of them
+
+// how far next steps at most before giving up, longer than any entry and line clear delay
+const NEXT_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum State {
+    Falling,
+    LineClear,
+    // between minos, or before the first one enters
+    Entry,
+    ToppedOut(TopOut),
+}
+
+pub struct Harness {
+    pub system: GameSystem,
+    pub clock: ManualClock,
+    // the state after every step and action, repeats left out
+    pub states: Vec<State>,
+    // whether the game asked to move on to the end screen
+    pub ended: bool,
+    dimensions: consts::Dimensions,
+    score: i32,
+}
+
+// a game to be played again, saved as TOML with the rows from the top down in the plain text board
+// format
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub board: Vec<String>,
+    // dealt before the randomizer takes over
+    pub pieces: String,
+    pub script: String,
+}
+
+impl Replay {
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|source| toml::from_str(&source).map_err(|error| error.to_string()))
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> Result<(), String> {
+        toml::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|source| std::fs::write(path, source).map_err(|error| error.to_string()))
+    }
+
+    pub fn play(&self) -> Harness {
+        let mut harness = Harness::new(&self.board.join("\n"), &self.pieces, self.seed);
+        harness.run(&self.script);
+        harness
+    }
+}
+
+impl Harness {
+    // a game with the default config on a board as wide as the text, dealing the pieces given as
+    // letters before the randomizer takes over
+    pub fn new(board: &str, pieces: &str, seed: u64) -> Self {
+        Self::with_config(board, pieces, seed, Config::default())
+    }
+
+    pub fn with_config(board: &str, pieces: &str, seed: u64, mut config: Config) -> Self {
+        let blocks = game::board_text::parse(board).unwrap();
+        if let Some(line) = blocks.first() {
+            config.dimensions =
+                consts::Dimensions::new(line.len() as i32, config.dimensions.visible_height);
+        }
+
+        let dimensions = config.dimensions;
+        let mut system = GameSystem::with_config(game::playing::DEFAULT_BINDINGS, seed, config);
+        system.set_blocks(blocks);
+        system.set_sequence(
+            pieces
+                .chars()
+                .map(|letter| game::puzzles::tetromino_index(letter).unwrap()),
+        );
+
+        // the first update only starts the clock
+        let clock = ManualClock::new();
+        system.update(&clock, &mut game::GameSystemFlow::Default);
+
+        let mut harness = Self {
+            system,
+            clock,
+            states: vec![],
+            ended: false,
+            dimensions,
+            score: 0,
+        };
+        harness.record();
+        harness.next();
+        harness
+    }
+
+    pub fn run(&mut self, script: &str) -> &mut Self {
+        for word in script.split_whitespace() {
+            let (name, count) = match word.split_once('*') {
+                Some((name, count)) => (name, count.parse().unwrap()),
+                None => (word, 1),
+            };
+
+            for _ in 0..count {
+                match name {
+                    "tick" => self.step(consts::NET_TICK_INTERVAL),
+                    "next" => self.next(),
+                    _ => self.action(match name {
+                        "left" => Action::MoveLeft,
+                        "right" => Action::MoveRight,
+                        "down" => Action::SoftDrop,
+                        "drop" => Action::HardDrop,
+                        "cw" => Action::RotateClockwise,
+                        "ccw" => Action::RotateCounterClockwise,
+                        "flip" => Action::RotateFlip,
+                        "hold" => Action::Hold,
+                        _ => panic!("unknown script word {}", word),
+                    }),
+                }
+            }
+        }
+        self
+    }
+
+    pub fn step(&mut self, delta_time: Duration) {
+        if self.ended {
+            return;
+        }
+        let mut flow = game::GameSystemFlow::Default;
+        self.clock.advance(delta_time);
+        self.system.update(&self.clock, &mut flow);
+        self.follow(flow);
+    }
+
+    pub fn action(&mut self, action: Action) {
+        if self.ended {
+            return;
+        }
+        let mut flow = game::GameSystemFlow::Default;
+        self.system.action(action, &mut flow);
+        self.follow(flow);
+    }
+
+    // steps until a mino is falling, which takes a gravity tick at the start and the entry delay
+    // after each placement
+    pub fn next(&mut self) {
+        let mut elapsed = Duration::ZERO;
+        while self.state() != State::Falling && !self.ended {
+            assert!(elapsed < NEXT_LIMIT, "no mino entered");
+            self.step(consts::NET_TICK_INTERVAL);
+            elapsed += consts::NET_TICK_INTERVAL;
+        }
+    }
+
+    // the locked blocks in the plain text format
+    pub fn board(&self) -> String {
+        game::board_text::format(self.system.context().blocks)
+    }
+
+    pub fn score(&self) -> i32 {
+        *self.system.context().score
+    }
+
+    pub fn lines(&self) -> i32 {
+        *self.system.context().lines
+    }
+
+    pub fn state(&self) -> State {
+        let context = self.system.context();
+        if let Some(top_out) = self.system.topped_out() {
+            State::ToppedOut(top_out)
+        } else if !context.clearing_lines.is_empty() {
+            State::LineClear
+        } else if context.active_mino.is_some() {
+            State::Falling
+        } else {
+            State::Entry
+        }
+    }
+
+    fn follow(&mut self, flow: game::GameSystemFlow) {
+        if let game::GameSystemFlow::To(game::GameSystem::End(_)) = flow {
+            self.ended = true;
+        }
+        self.check();
+        self.record();
+    }
+
+    fn check(&mut self) {
+        let context = self.system.context();
+        let blocks = context.blocks;
+
+        assert_eq!(
+            *context.dimensions, self.dimensions,
+            "the dimensions changed"
+        );
+        assert_eq!(
+            blocks.len(),
+            self.dimensions.height as usize,
+            "the height changed"
+        );
+        assert!(
+            blocks
+                .iter()
+                .all(|line| line.len() == self.dimensions.width as usize),
+            "the width changed"
+        );
+
+        // a mino spawning into the stack is left there as the game tops out
+        if let (Some(mino), None) = (context.active_mino, self.system.topped_out()) {
+            for (x, y) in mino.blocks.iter() {
+                let (x, y) = (mino.x + x, mino.y + y);
+                let free = (0..self.dimensions.width).contains(&x)
+                    && (0..self.dimensions.height).contains(&y)
+                    && blocks[y as usize][x as usize].is_none();
+                assert!(free, "the active mino overlaps the board at {}, {}", x, y);
+            }
+        }
+
+        for (row, line) in blocks.iter().enumerate() {
+            if line.iter().all(Option::is_some) {
+                assert!(
+                    context.clearing_lines.contains(&row),
+                    "row {} is full after the clear",
+                    row
+                );
+            }
+        }
+
+        assert!(self.score <= *context.score, "the score went down");
+        self.score = *context.score;
+
+        assert_eq!(
+            self.ended,
+            self.system.topped_out().is_some(),
+            "the game ended without topping out, or topped out without ending"
+        );
+    }
+
+    fn record(&mut self) {
+        let state = self.state();
+        if self.states.last() != Some(&state) {
+            self.states.push(state);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+use std::net::TcpListener;
+
+use crate::net::protocol::PROTOCOL_VERSION;
+use crate::net::Transport;
+use crate::{game, net};
+
+pub struct GameContext<'a> {
+    pub status: &'a String,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    listener: Option<TcpListener>,
+    connection: Option<net::Connection>,
+    local: usize,
+    netcode: net::Netcode,
+
+    status: String,
+}
+
+impl GameSystem {
+    pub fn host(port: u16, netcode: net::Netcode) -> Self {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener));
+
+        let (listener, status) = match listener {
+            Ok(listener) => (
+                Some(listener),
+                format!("WAITING FOR OPPONENT ON PORT {}", port),
+            ),
+            Err(err) => (None, format!("FAILED TO HOST: {}", err)),
+        };
+
+        Self {
+            pressed: HashSet::new(),
+
+            listener,
+            connection: None,
+            local: 0,
+            netcode,
+
+            status,
+        }
+    }
+
+    pub fn connect(addr: &str) -> Self {
+        let connection = net::Connection::connect(addr).and_then(|mut connection| {
+            let hello = net::Message::Hello {
+                version: PROTOCOL_VERSION,
+            };
+            connection.send(&hello).map(|_| connection)
+        });
+
+        let (connection, status) = match connection {
+            Ok(connection) => (Some(connection), format!("CONNECTING TO {}", addr)),
+            Err(err) => (None, format!("FAILED TO CONNECT: {}", err)),
+        };
+
+        Self {
+            pressed: HashSet::new(),
+
+            listener: None,
+            connection,
+            local: 1,
+            // the host decides the netcode in its welcome message
+            netcode: net::Netcode::Lockstep,
+
+            status,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    if code == KeyCode::Escape {
+                        let state = game::GameSystem::Start(game::start::GameSystem::new());
+                        *flow = game::GameSystemFlow::To(state);
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        if let Some(listener) = self.listener.as_ref() {
+            match net::Connection::accept(listener) {
+                Ok(Some(connection)) => {
+                    self.listener = None;
+                    self.connection = Some(connection);
+                    self.status = "OPPONENT FOUND".to_string();
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.listener = None;
+                    self.status = format!("FAILED TO ACCEPT: {}", err);
+                }
+            }
+        }
+
+        while let Some(event) = self.connection.as_mut().and_then(|c| c.poll()) {
+            match event {
+                net::Event::Message(net::Message::Hello { version }) if self.local == 0 => {
+                    if version != PROTOCOL_VERSION {
+                        self.fail(version);
+                        return;
+                    }
+
+                    let seed = rand::random();
+                    let welcome = net::Message::Welcome {
+                        version: PROTOCOL_VERSION,
+                        seed,
+                        netcode: self.netcode,
+                    };
+                    let mut connection = self.connection.take().unwrap();
+                    if connection.send(&welcome).is_err() {
+                        self.status = "CONNECTION LOST".to_string();
+                        return;
+                    }
+
+                    self.start(connection, seed, flow);
+                    return;
+                }
+                net::Event::Message(net::Message::Welcome {
+                    version,
+                    seed,
+                    netcode,
+                }) if self.local == 1 => {
+                    if version != PROTOCOL_VERSION {
+                        self.fail(version);
+                        return;
+                    }
+
+                    self.netcode = netcode;
+                    let connection = self.connection.take().unwrap();
+                    self.start(connection, seed, flow);
+                    return;
+                }
+                net::Event::Message(_) => {}
+                net::Event::Disconnected => {
+                    self.connection = None;
+                    self.status = "CONNECTION LOST".to_string();
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, version: u16) {
+        self.connection = None;
+        self.status = format!(
+            "PROTOCOL VERSION MISMATCH: {} (EXPECTED {})",
+            version, PROTOCOL_VERSION
+        );
+    }
+
+    fn start(&mut self, connection: net::Connection, seed: u64, flow: &mut game::GameSystemFlow) {
+        let state = game::GameSystem::Online(game::online::GameSystem::new(
+            self.netcode,
+            connection,
+            self.local,
+            seed,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            status: &self.status,
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub level: &'a u32,
+    // where the level stops until a line is cleared
+    pub level_stop: u32,
+    pub score: &'a i32,
+    pub grade: &'static str,
+    pub elapsed: &'a Duration,
+}
+
+// climbs from level 0 to 999 under the TGM speed curve, graded by score and time
+pub struct GameSystem {
+    board: game::playing::GameSystem,
+
+    last_update: Option<Duration>,
+    elapsed: Duration,
+
+    level: u32,
+    score: i32,
+    grade: usize,
+    grand_master: bool,
+    // what the board had counted when last checked
+    seen_pieces: i32,
+    seen_lines: i32,
+    // the time each finished section took
+    sections: Vec<Duration>,
+    section_start: Duration,
+}
+
+impl GameSystem {
+    pub fn new() -> Self {
+        let board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            rand::random(),
+            game::playing::Config::master(),
+        );
+
+        let mut system = Self {
+            board,
+
+            last_update: None,
+            elapsed: Duration::ZERO,
+
+            level: 0,
+            score: 0,
+            grade: 0,
+            grand_master: true,
+            // the first piece enters at level 0
+            seen_pieces: 1,
+            seen_lines: 0,
+            sections: vec![],
+            section_start: Duration::ZERO,
+        };
+        system.apply_timing();
+        system
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_advance(flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if let Some(delta_time) = delta_time {
+            if !*self.board.context().paused {
+                self.elapsed += delta_time;
+            }
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_advance(flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    // lines move the level past a stop, while entering pieces only move it up to the next one
+    fn level_stop(&self) -> u32 {
+        if (consts::TGM_MAX_LEVEL / consts::TGM_SECTION_LEVELS) * consts::TGM_SECTION_LEVELS
+            <= self.level
+        {
+            consts::TGM_MAX_LEVEL - 1
+        } else {
+            (self.level / consts::TGM_SECTION_LEVELS + 1) * consts::TGM_SECTION_LEVELS - 1
+        }
+    }
+
+    // scores the lines cleared and counts the pieces entered since the last check
+    fn check_and_advance(&mut self, flow: &mut game::GameSystemFlow) {
+        let lines = *self.board.context().lines;
+        let pieces = self.board.pieces();
+
+        let cleared = lines - self.seen_lines;
+        if 0 < cleared {
+            // the TGM score, leaving out the soft drop, combo and bravo bonuses
+            self.score += (self.level as i32 + cleared + 3) / 4 * cleared;
+            while consts::TGM_GRADES
+                .get(self.grade + 1)
+                .is_some_and(|(score, _)| *score <= self.score)
+            {
+                self.grade += 1;
+            }
+
+            self.advance_level((self.level + cleared as u32).min(consts::TGM_MAX_LEVEL));
+        }
+        for _ in self.seen_pieces..pieces {
+            self.advance_level((self.level + 1).min(self.level_stop()));
+        }
+        self.seen_lines = lines;
+        self.seen_pieces = self.seen_pieces.max(pieces);
+
+        if consts::TGM_MAX_LEVEL <= self.level {
+            self.finish(None, flow);
+        }
+    }
+
+    fn advance_level(&mut self, level: u32) {
+        let previous = std::mem::replace(&mut self.level, level);
+
+        if previous / consts::TGM_SECTION_LEVELS < level / consts::TGM_SECTION_LEVELS
+            || (previous < consts::TGM_MAX_LEVEL && consts::TGM_MAX_LEVEL <= level)
+        {
+            self.sections.push(self.elapsed - self.section_start);
+            self.section_start = self.elapsed;
+        }
+
+        for (checkpoint, time, score) in consts::TGM_GM_CHECKPOINTS.iter() {
+            if previous < *checkpoint
+                && *checkpoint <= level
+                && (*time < self.elapsed || self.score < *score)
+            {
+                self.grand_master = false;
+            }
+        }
+
+        if previous != level {
+            self.apply_timing();
+        }
+    }
+
+    // sets the gravity and delays of the current level on the board
+    fn apply_timing(&mut self) {
+        let gravity = consts::TGM_GRAVITY
+            .iter()
+            .rev()
+            .find(|(level, _)| *level <= self.level)
+            .map(|(_, gravity)| *gravity)
+            .unwrap();
+        let [entry, line_clear, lock, auto_shift] = consts::TGM_DELAYS
+            .iter()
+            .rev()
+            .find(|(level, _)| *level <= self.level)
+            .map(|(_, delays)| *delays)
+            .unwrap();
+
+        self.board.set_timing(
+            game::playing::Gravity::Fixed(consts::TGM_FRAME * 256 / gravity),
+            consts::TGM_FRAME * entry,
+            consts::TGM_FRAME * line_clear,
+            Some(consts::TGM_FRAME * lock),
+            Some(game::playing::AutoShift {
+                delay: consts::TGM_FRAME * auto_shift,
+                repeat: consts::TGM_FRAME * consts::TGM_DAS_REPEAT,
+            }),
+        );
+    }
+
+    fn grade_name(&self) -> &'static str {
+        if self.grand_master && consts::TGM_MAX_LEVEL <= self.level {
+            "GM"
+        } else {
+            consts::TGM_GRADES[self.grade].1
+        }
+    }
+
+    // a transition out of the board means it topped out
+    fn check_and_finish(
+        &mut self,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        if let game::GameSystemFlow::To(_) = board_flow {
+            self.finish(self.board.topped_out(), flow);
+        }
+    }
+
+    fn finish(&self, top_out: Option<game::playing::TopOut>, flow: &mut game::GameSystemFlow) {
+        let state = game::GameSystem::MasterEnd(game::master_end::GameSystem::new(
+            self.board.context().blocks.clone(),
+            self.board.config().clone(),
+            self.grade_name(),
+            self.level,
+            self.elapsed,
+            self.sections.clone(),
+            top_out,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            level: &self.level,
+            level_stop: self.level_stop(),
+            score: &self.score,
+            grade: self.grade_name(),
+            elapsed: &self.elapsed,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{consts, game};
+
+// a list of items picked with the arrows and return or with the mouse, laid out in blocks from the
+// center of the board so the renderer and the cursor agree on where each item is
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<Item>,
+    pub selected: usize,
+    cursor: (f32, f32),
+}
+
+pub struct Item {
+    pub label: String,
+    // shown after the label, for a setting and its value
+    pub value: Option<String>,
+    pub enabled: bool,
+}
+
+impl Item {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: None,
+            enabled: true,
+        }
+    }
+
+    pub fn with_value(self, value: impl Into<String>) -> Self {
+        Self {
+            value: Some(value.into()),
+            ..self
+        }
+    }
+
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Choice {
+    Chosen(usize),
+    // left and right on an item, stepping through the values of a setting
+    Previous(usize),
+    Next(usize),
+    Back,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<String>, items: Vec<Item>) -> Self {
+        let mut menu = Self {
+            title: title.into(),
+            items: vec![],
+            selected: 0,
+            cursor: (f32::NAN, f32::NAN),
+        };
+        menu.set_items(items);
+        menu
+    }
+
+    // swaps the items in place, keeping the selection where it was when it is still there
+    pub fn set_items(&mut self, items: Vec<Item>) {
+        self.items = items;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+        if !self.is_enabled(self.selected) {
+            self.move_selection(1);
+        }
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.is_enabled(index) {
+            self.selected = index;
+        }
+    }
+
+    fn is_enabled(&self, index: usize) -> bool {
+        self.items.get(index).is_some_and(|item| item.enabled)
+    }
+
+    // moves to the next enabled item that way, wrapping around the ends
+    fn move_selection(&mut self, step: isize) {
+        let count = self.items.len() as isize;
+        let mut index = self.selected as isize;
+        for _ in 0..count {
+            index = (index + step).rem_euclid(count);
+            if self.is_enabled(index as usize) {
+                self.selected = index as usize;
+                return;
+            }
+        }
+    }
+
+    // the state filters out held keys before passing a press on
+    pub fn key(&mut self, code: winit::keyboard::KeyCode) -> Option<Choice> {
+        use winit::keyboard::KeyCode;
+
+        match code {
+            KeyCode::ArrowUp => self.move_selection(-1),
+            KeyCode::ArrowDown => self.move_selection(1),
+            KeyCode::Enter | KeyCode::Space if self.is_enabled(self.selected) => {
+                return Some(Choice::Chosen(self.selected));
+            }
+            KeyCode::ArrowLeft if self.is_enabled(self.selected) => {
+                return Some(Choice::Previous(self.selected));
+            }
+            KeyCode::ArrowRight if self.is_enabled(self.selected) => {
+                return Some(Choice::Next(self.selected));
+            }
+            KeyCode::Escape | KeyCode::Backspace => return Some(Choice::Back),
+            _ => {}
+        }
+        None
+    }
+
+    // hovering selects an item and a left click chooses it, while a right click goes back
+    pub fn mouse_input(&mut self, input: &game::MouseInput) -> Option<Choice> {
+        use winit::event::{ElementState, MouseButton};
+
+        match *input {
+            game::MouseInput::Moved { x, y } => {
+                self.cursor = (x, y);
+                if let Some(index) = self.item_at(x, y) {
+                    self.select(index);
+                }
+                None
+            }
+            game::MouseInput::Button {
+                button: MouseButton::Left,
+                state: ElementState::Pressed,
+            } => {
+                let (x, y) = self.cursor;
+                self.item_at(x, y)
+                    .filter(|index| self.is_enabled(*index))
+                    .map(Choice::Chosen)
+            }
+            game::MouseInput::Button {
+                button: MouseButton::Right,
+                state: ElementState::Pressed,
+            } => Some(Choice::Back),
+            _ => None,
+        }
+    }
+
+    // the items on screen, scrolled just far enough to keep the selection in view
+    pub fn visible(&self) -> std::ops::Range<usize> {
+        let start = (self.selected + 1).saturating_sub(consts::MENU_MAX_ROWS);
+        start..self.items.len().min(start + consts::MENU_MAX_ROWS)
+    }
+
+    // the center of an item on screen, the rows centered a little below the middle of the board
+    pub fn item_position(&self, index: usize) -> (f32, f32) {
+        let visible = self.visible();
+        let top =
+            (visible.len() as f32 - 1.0) * consts::MENU_ROW_HEIGHT * 0.5 + consts::MENU_CENTER;
+        let row = index as f32 - visible.start as f32;
+        (0.0, top - row * consts::MENU_ROW_HEIGHT)
+    }
+
+    // the title sits two rows above the first item
+    pub fn title_position(&self) -> (f32, f32) {
+        let (x, y) = self.item_position(self.visible().start);
+        (x, y + consts::MENU_ROW_HEIGHT * 2.0)
+    }
+
+    fn item_at(&self, x: f32, y: f32) -> Option<usize> {
+        if consts::MENU_WIDTH * 0.5 < x.abs() {
+            return None;
+        }
+        self.visible().find(|index| {
+            let (_, center) = self.item_position(*index);
+            (y - center).abs() <= consts::MENU_ROW_HEIGHT * 0.5
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::event::{ElementState, MouseButton};
+    use winit::keyboard::KeyCode;
+
+    fn menu() -> Menu {
+        Menu::new(
+            "MENU",
+            vec![
+                Item::new("A"),
+                Item::new("B").enabled(false),
+                Item::new("C").with_value("1"),
+            ],
+        )
+    }
+
+    #[test]
+    fn arrows_skip_disabled_items_and_wrap() {
+        let mut menu = menu();
+        assert_eq!(menu.key(KeyCode::ArrowDown), None);
+        assert_eq!(menu.selected, 2);
+        menu.key(KeyCode::ArrowDown);
+        assert_eq!(menu.selected, 0);
+        menu.key(KeyCode::ArrowUp);
+        assert_eq!(menu.selected, 2);
+
+        assert_eq!(menu.key(KeyCode::Enter), Some(Choice::Chosen(2)));
+        assert_eq!(menu.key(KeyCode::ArrowLeft), Some(Choice::Previous(2)));
+        assert_eq!(menu.key(KeyCode::ArrowRight), Some(Choice::Next(2)));
+        assert_eq!(menu.key(KeyCode::Escape), Some(Choice::Back));
+    }
+
+    #[test]
+    fn the_cursor_selects_and_clicks_the_item_under_it() {
+        let mut menu = menu();
+        let click = game::MouseInput::Button {
+            button: MouseButton::Left,
+            state: ElementState::Pressed,
+        };
+
+        let (x, y) = menu.item_position(2);
+        menu.mouse_input(&game::MouseInput::Moved { x, y: y + 0.4 });
+        assert_eq!(menu.selected, 2);
+        assert_eq!(menu.mouse_input(&click), Some(Choice::Chosen(2)));
+
+        // a disabled item can't be picked, and neither can the space beside the items
+        let (x, y) = menu.item_position(1);
+        menu.mouse_input(&game::MouseInput::Moved { x, y });
+        assert_eq!(menu.selected, 2);
+        assert_eq!(menu.mouse_input(&click), None);
+
+        let (_, y) = menu.item_position(0);
+        menu.mouse_input(&game::MouseInput::Moved { x: 20.0, y });
+        assert_eq!(menu.mouse_input(&click), None);
+    }
+
+    #[test]
+    fn long_menus_scroll_with_the_selection() {
+        let items = (0..consts::MENU_MAX_ROWS + 3)
+            .map(|index| Item::new(index.to_string()))
+            .collect();
+        let mut menu = Menu::new("MENU", items);
+        assert_eq!(menu.visible(), 0..consts::MENU_MAX_ROWS);
+
+        for _ in 0..consts::MENU_MAX_ROWS + 1 {
+            menu.key(KeyCode::ArrowDown);
+        }
+        assert_eq!(menu.visible(), 2..consts::MENU_MAX_ROWS + 2);
+        assert_eq!(
+            menu.item_position(menu.selected),
+            menu.item_position(menu.visible().end - 1)
+        );
+    }
+}
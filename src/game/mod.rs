@@ -1,38 +1,261 @@
+pub mod board_text;
+pub mod bot;
+pub mod cheese;
+pub mod cheese_end;
+pub mod classic;
+pub mod clock;
+pub mod editor;
 pub mod end;
+pub mod fumen;
+#[cfg(test)]
+mod fuzz;
+pub mod garbage;
+#[cfg(test)]
+pub mod harness;
+pub mod lobby;
+pub mod master;
+pub mod master_end;
+pub mod menu;
+pub mod online;
+pub mod pieces;
 pub mod playing;
+pub mod practice;
+pub mod puzzle;
+pub mod puzzle_end;
+pub mod puzzle_select;
+pub mod puzzles;
+pub mod records;
+pub mod rotation;
+pub mod royale;
+pub mod royale_end;
+pub mod script;
+pub mod script_end;
+pub mod scripted;
 pub mod start;
+pub mod survival;
+pub mod survival_end;
+pub mod versus;
+pub mod versus_end;
 
 pub enum GameContext<'a> {
-    Start(start::GameContext),
+    Start(start::GameContext<'a>),
     Playing(playing::GameContext<'a>),
     End(end::GameContext<'a>),
+    Versus(versus::GameContext<'a>),
+    VersusEnd(versus_end::GameContext<'a>),
+    Lobby(lobby::GameContext<'a>),
+    Online(online::GameContext<'a>),
+    Royale(royale::GameContext<'a>),
+    RoyaleEnd(royale_end::GameContext<'a>),
+    Cheese(cheese::GameContext<'a>),
+    CheeseEnd(cheese_end::GameContext<'a>),
+    Survival(survival::GameContext<'a>),
+    SurvivalEnd(survival_end::GameContext<'a>),
+    Classic(classic::GameContext<'a>),
+    Master(master::GameContext<'a>),
+    MasterEnd(master_end::GameContext<'a>),
+    Practice(practice::GameContext<'a>),
+    PuzzleSelect(puzzle_select::GameContext<'a>),
+    Puzzle(puzzle::GameContext<'a>),
+    PuzzleEnd(puzzle_end::GameContext<'a>),
+    Editor(editor::GameContext<'a>),
+    Scripted(scripted::GameContext<'a>),
+    ScriptEnd(script_end::GameContext<'a>),
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum GameSystem {
     Start(start::GameSystem),
     Playing(playing::GameSystem),
     End(end::GameSystem),
+    Versus(versus::GameSystem),
+    VersusEnd(versus_end::GameSystem),
+    Lobby(lobby::GameSystem),
+    Online(online::GameSystem),
+    Royale(royale::GameSystem),
+    RoyaleEnd(royale_end::GameSystem),
+    Cheese(cheese::GameSystem),
+    CheeseEnd(cheese_end::GameSystem),
+    Survival(survival::GameSystem),
+    SurvivalEnd(survival_end::GameSystem),
+    Classic(classic::GameSystem),
+    Master(master::GameSystem),
+    MasterEnd(master_end::GameSystem),
+    Practice(practice::GameSystem),
+    PuzzleSelect(puzzle_select::GameSystem),
+    Puzzle(puzzle::GameSystem),
+    PuzzleEnd(puzzle_end::GameSystem),
+    Editor(editor::GameSystem),
+    Scripted(scripted::GameSystem),
+    ScriptEnd(script_end::GameSystem),
 }
 
 impl GameSystem {
-    pub fn input(&mut self, input: &winit::event::KeyEvent) {
+    pub fn input(&mut self, input: &winit::event::KeyEvent) -> Vec<GameEvent> {
         let mut flow = GameSystemFlow::Default;
+
+        // escape leaves a game for the menu, where it waits paused to be continued
+        if let GameSystem::Playing(system) = self {
+            let escape = winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape);
+            if input.physical_key == escape && input.state == winit::event::ElementState::Pressed {
+                let mut board = system.clone();
+                board.set_paused(true);
+                board.release_keys();
+                let state = GameSystem::Start(start::GameSystem::with_suspended(board));
+                return self.finish(GameSystemFlow::To(state));
+            }
+        }
+
         match self {
             GameSystem::Start(system) => system.input(input, &mut flow),
             GameSystem::Playing(system) => system.input(input, &mut flow),
             GameSystem::End(system) => system.input(input, &mut flow),
+            GameSystem::Versus(system) => system.input(input, &mut flow),
+            GameSystem::VersusEnd(system) => system.input(input, &mut flow),
+            GameSystem::Lobby(system) => system.input(input, &mut flow),
+            GameSystem::Online(system) => system.input(input, &mut flow),
+            GameSystem::Royale(system) => system.input(input, &mut flow),
+            GameSystem::RoyaleEnd(system) => system.input(input, &mut flow),
+            GameSystem::Cheese(system) => system.input(input, &mut flow),
+            GameSystem::CheeseEnd(system) => system.input(input, &mut flow),
+            GameSystem::Survival(system) => system.input(input, &mut flow),
+            GameSystem::SurvivalEnd(system) => system.input(input, &mut flow),
+            GameSystem::Classic(system) => system.input(input, &mut flow),
+            GameSystem::Master(system) => system.input(input, &mut flow),
+            GameSystem::MasterEnd(system) => system.input(input, &mut flow),
+            GameSystem::Practice(system) => system.input(input, &mut flow),
+            GameSystem::PuzzleSelect(system) => system.input(input, &mut flow),
+            GameSystem::Puzzle(system) => system.input(input, &mut flow),
+            GameSystem::PuzzleEnd(system) => system.input(input, &mut flow),
+            GameSystem::Editor(system) => system.input(input, &mut flow),
+            GameSystem::Scripted(system) => system.input(input, &mut flow),
+            GameSystem::ScriptEnd(system) => system.input(input, &mut flow),
         }
-        flow.apply(self);
+        self.finish(flow)
     }
 
-    pub fn update(&mut self) {
+    // only the editor and the menus listen to the mouse
+    pub fn mouse_input(&mut self, input: &MouseInput) -> Vec<GameEvent> {
         let mut flow = GameSystemFlow::Default;
         match self {
-            GameSystem::Start(system) => system.update(&mut flow),
-            GameSystem::Playing(system) => system.update(&mut flow),
-            GameSystem::End(system) => system.update(&mut flow),
+            GameSystem::Start(system) => system.mouse_input(input, &mut flow),
+            GameSystem::PuzzleSelect(system) => system.mouse_input(input, &mut flow),
+            GameSystem::Editor(system) => system.mouse_input(input, &mut flow),
+            _ => {}
+        }
+        self.finish(flow)
+    }
+
+    pub fn update(&mut self, clock: &dyn clock::Clock) -> Vec<GameEvent> {
+        let mut flow = GameSystemFlow::Default;
+        match self {
+            GameSystem::Start(system) => system.update(clock, &mut flow),
+            GameSystem::Playing(system) => system.update(clock, &mut flow),
+            GameSystem::End(system) => system.update(clock, &mut flow),
+            GameSystem::Versus(system) => system.update(clock, &mut flow),
+            GameSystem::VersusEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Lobby(system) => system.update(clock, &mut flow),
+            GameSystem::Online(system) => system.update(clock, &mut flow),
+            GameSystem::Royale(system) => system.update(clock, &mut flow),
+            GameSystem::RoyaleEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Cheese(system) => system.update(clock, &mut flow),
+            GameSystem::CheeseEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Survival(system) => system.update(clock, &mut flow),
+            GameSystem::SurvivalEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Classic(system) => system.update(clock, &mut flow),
+            GameSystem::Master(system) => system.update(clock, &mut flow),
+            GameSystem::MasterEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Practice(system) => system.update(clock, &mut flow),
+            GameSystem::PuzzleSelect(system) => system.update(clock, &mut flow),
+            GameSystem::Puzzle(system) => system.update(clock, &mut flow),
+            GameSystem::PuzzleEnd(system) => system.update(clock, &mut flow),
+            GameSystem::Editor(system) => system.update(clock, &mut flow),
+            GameSystem::Scripted(system) => system.update(clock, &mut flow),
+            GameSystem::ScriptEnd(system) => system.update(clock, &mut flow),
+        }
+        self.finish(flow)
+    }
+
+    // takes the events of the local boards and moves on to the next state, if there is one
+    fn finish(&mut self, flow: GameSystemFlow) -> Vec<GameEvent> {
+        let mut events = self.take_events();
+        match &flow {
+            GameSystemFlow::To(system) => {
+                events.push(GameEvent::StateChanged {
+                    from: self.name(),
+                    to: system.name(),
+                });
+            }
+            GameSystemFlow::Quit => events.push(GameEvent::Quit),
+            GameSystemFlow::Default => {}
+        }
+
+        // a game that ended goes into the high scores and the saved replays
+        if let (GameSystem::Playing(board), GameSystemFlow::To(GameSystem::End(_))) =
+            (&*self, &flow)
+        {
+            let mut records = records::Records::load();
+            records.add(board);
+            records.save();
         }
+
         flow.apply(self);
+        events
+    }
+
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        let boards = match self {
+            GameSystem::Playing(system) => vec![system.take_events()],
+            GameSystem::Versus(system) => system.take_events().into(),
+            GameSystem::Royale(system) => vec![system.take_events()],
+            GameSystem::Cheese(system) => vec![system.take_events()],
+            GameSystem::Survival(system) => vec![system.take_events()],
+            GameSystem::Master(system) => vec![system.take_events()],
+            GameSystem::Practice(system) => vec![system.take_events()],
+            GameSystem::Puzzle(system) => vec![system.take_events()],
+            GameSystem::Scripted(system) => vec![system.take_events()],
+            // online boards are left out, as rollback plays their frames again
+            _ => vec![],
+        };
+
+        boards
+            .into_iter()
+            .enumerate()
+            .flat_map(|(player, events)| {
+                events
+                    .into_iter()
+                    .map(move |event| GameEvent::Board { player, event })
+            })
+            .collect()
+    }
+
+    // the name of the state, as the module it lives in
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameSystem::Start(_) => "start",
+            GameSystem::Playing(_) => "playing",
+            GameSystem::End(_) => "end",
+            GameSystem::Versus(_) => "versus",
+            GameSystem::VersusEnd(_) => "versus_end",
+            GameSystem::Lobby(_) => "lobby",
+            GameSystem::Online(_) => "online",
+            GameSystem::Royale(_) => "royale",
+            GameSystem::RoyaleEnd(_) => "royale_end",
+            GameSystem::Cheese(_) => "cheese",
+            GameSystem::CheeseEnd(_) => "cheese_end",
+            GameSystem::Survival(_) => "survival",
+            GameSystem::SurvivalEnd(_) => "survival_end",
+            GameSystem::Classic(_) => "classic",
+            GameSystem::Master(_) => "master",
+            GameSystem::MasterEnd(_) => "master_end",
+            GameSystem::Practice(_) => "practice",
+            GameSystem::PuzzleSelect(_) => "puzzle_select",
+            GameSystem::Puzzle(_) => "puzzle",
+            GameSystem::PuzzleEnd(_) => "puzzle_end",
+            GameSystem::Editor(_) => "editor",
+            GameSystem::Scripted(_) => "scripted",
+            GameSystem::ScriptEnd(_) => "script_end",
+        }
     }
 
     pub fn context(&'_ self) -> GameContext<'_> {
@@ -40,13 +263,63 @@ impl GameSystem {
             GameSystem::Start(system) => GameContext::Start(system.context()),
             GameSystem::Playing(system) => GameContext::Playing(system.context()),
             GameSystem::End(system) => GameContext::End(system.context()),
+            GameSystem::Versus(system) => GameContext::Versus(system.context()),
+            GameSystem::VersusEnd(system) => GameContext::VersusEnd(system.context()),
+            GameSystem::Lobby(system) => GameContext::Lobby(system.context()),
+            GameSystem::Online(system) => GameContext::Online(system.context()),
+            GameSystem::Royale(system) => GameContext::Royale(system.context()),
+            GameSystem::RoyaleEnd(system) => GameContext::RoyaleEnd(system.context()),
+            GameSystem::Cheese(system) => GameContext::Cheese(system.context()),
+            GameSystem::CheeseEnd(system) => GameContext::CheeseEnd(system.context()),
+            GameSystem::Survival(system) => GameContext::Survival(system.context()),
+            GameSystem::SurvivalEnd(system) => GameContext::SurvivalEnd(system.context()),
+            GameSystem::Classic(system) => GameContext::Classic(system.context()),
+            GameSystem::Master(system) => GameContext::Master(system.context()),
+            GameSystem::MasterEnd(system) => GameContext::MasterEnd(system.context()),
+            GameSystem::Practice(system) => GameContext::Practice(system.context()),
+            GameSystem::PuzzleSelect(system) => GameContext::PuzzleSelect(system.context()),
+            GameSystem::Puzzle(system) => GameContext::Puzzle(system.context()),
+            GameSystem::PuzzleEnd(system) => GameContext::PuzzleEnd(system.context()),
+            GameSystem::Editor(system) => GameContext::Editor(system.context()),
+            GameSystem::Scripted(system) => GameContext::Scripted(system.context()),
+            GameSystem::ScriptEnd(system) => GameContext::ScriptEnd(system.context()),
         }
     }
 }
 
+// what observers see after each input and update, in the order it happened
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    // on a local board, player 0 being the only or the left one
+    Board {
+        player: usize,
+        event: playing::Event,
+    },
+    StateChanged {
+        from: &'static str,
+        to: &'static str,
+    },
+    // picked from the menu, for the window to close
+    Quit,
+}
+
+// the cursor is measured in blocks from the center of the board, up being positive
+pub enum MouseInput {
+    Moved {
+        x: f32,
+        y: f32,
+    },
+    Button {
+        button: winit::event::MouseButton,
+        state: winit::event::ElementState,
+    },
+}
+
+#[allow(clippy::large_enum_variant)]
 pub enum GameSystemFlow {
     Default,
     To(GameSystem),
+    Quit,
 }
 
 impl GameSystemFlow {
@@ -0,0 +1,114 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::{consts, game, net};
+
+pub struct GameContext<'a> {
+    pub boards: [game::playing::GameContext<'a>; 2],
+    pub local: &'a usize,
+    pub stalled: &'a bool,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+    last_update: Option<Duration>,
+    remaining_time: Duration,
+
+    session: net::Session<net::Connection>,
+    local: usize,
+
+    stalled: bool,
+}
+
+impl GameSystem {
+    pub fn new(
+        netcode: net::Netcode,
+        connection: net::Connection,
+        local: usize,
+        seed: u64,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+            last_update: None,
+            remaining_time: Duration::ZERO,
+
+            session: net::Session::new(netcode, connection, local, seed),
+            local,
+
+            stalled: false,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, _flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    if let Some(action) = self.session.boards()[self.local].binding(code) {
+                        self.session.add_local_action(action);
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if let Some(delta_time) = delta_time {
+            self.remaining_time += delta_time;
+        }
+
+        while consts::NET_TICK_INTERVAL <= self.remaining_time {
+            match self.session.advance_frame() {
+                net::Status::Running => {
+                    self.stalled = false;
+                    self.remaining_time -= consts::NET_TICK_INTERVAL;
+                }
+                net::Status::Stalled => {
+                    self.stalled = true;
+                    self.remaining_time = consts::NET_TICK_INTERVAL;
+                    break;
+                }
+                net::Status::Finished(outcome) => {
+                    self.finish(outcome, flow);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn finish(&self, outcome: game::versus_end::Outcome, flow: &mut game::GameSystemFlow) {
+        let boards = self.session.boards().each_ref().map(|board| {
+            let cx = board.context();
+            (cx.blocks.clone(), *cx.score)
+        });
+        let dimensions = self.session.boards()[0].dimensions();
+        let state = game::GameSystem::VersusEnd(game::versus_end::GameSystem::new(
+            boards, dimensions, outcome, true,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            boards: self
+                .session
+                .boards()
+                .each_ref()
+                .map(|board| board.context()),
+            local: &self.local,
+            stalled: &self.stalled,
+        }
+    }
+}
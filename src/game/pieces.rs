@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use crate::consts;
+
+// the piece sets shipped with the game, the first one being the default
+const BUILTIN_SOURCES: &[&str] = &[
+    include_str!("../../assets/pieces/tetromino.toml"),
+    include_str!("../../assets/pieces/pentomino.toml"),
+    include_str!("../../assets/pieces/polyomino.toml"),
+    include_str!("../../assets/pieces/beginner.toml"),
+];
+
+#[derive(Debug)]
+pub struct PieceSet {
+    pub name: String,
+    pub pieces: Vec<Piece>,
+}
+
+#[derive(Debug)]
+pub struct Piece {
+    pub color: consts::BlockColor,
+    // cells of each orientation, turning clockwise from the spawn orientation
+    pub orientations: [Vec<(i32, i32)>; 4],
+    // offsets tried in order when turning from one orientation to another, indexed [from][to]
+    pub kicks: [[Vec<(i32, i32)>; 4]; 4],
+    // where the piece spawns relative to the spawn position of the board
+    pub spawn: (i32, i32),
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "failed to read piece set: {}", error),
+            LoadError::Parse(error) => write!(f, "failed to parse piece set: {}", error),
+            LoadError::Invalid(reason) => write!(f, "invalid piece set: {}", reason),
+        }
+    }
+}
+
+// the layout of a piece set file, see assets/pieces
+#[derive(serde::Deserialize)]
+struct PieceSetFile {
+    name: String,
+    #[serde(default)]
+    kicks: HashMap<String, HashMap<String, Vec<[i32; 2]>>>,
+    pieces: Vec<PieceFile>,
+}
+
+#[derive(serde::Deserialize)]
+struct PieceFile {
+    name: String,
+    color: consts::BlockColor,
+    // orientations left out are rotated clockwise within a box of this size
+    size: Option<i32>,
+    cells: Vec<Vec<[i32; 2]>>,
+    kicks: Option<String>,
+    #[serde(default)]
+    spawn: [i32; 2],
+}
+
+impl PieceSet {
+    pub fn builtin() -> &'static [Arc<PieceSet>] {
+        static BUILTIN: OnceLock<Vec<Arc<PieceSet>>> = OnceLock::new();
+
+        BUILTIN.get_or_init(|| {
+            BUILTIN_SOURCES
+                .iter()
+                .map(|source| Arc::new(Self::parse(source).expect("invalid built-in piece set")))
+                .collect()
+        })
+    }
+
+    // a built-in set by its name, or else a piece set file at the given path
+    pub fn find(name_or_path: &str) -> Result<Arc<PieceSet>, LoadError> {
+        let builtin = Self::builtin()
+            .iter()
+            .find(|piece_set| piece_set.name.eq_ignore_ascii_case(name_or_path));
+
+        match builtin {
+            Some(piece_set) => Ok(piece_set.clone()),
+            None => Self::load(name_or_path).map(Arc::new),
+        }
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<PieceSet, LoadError> {
+        let source = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+        Self::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<PieceSet, LoadError> {
+        let file = toml::from_str::<PieceSetFile>(source).map_err(LoadError::Parse)?;
+
+        if file.pieces.is_empty() {
+            return Err(LoadError::Invalid(format!("{} has no pieces", file.name)));
+        }
+
+        let pieces = file
+            .pieces
+            .into_iter()
+            .map(|piece| Self::parse_piece(piece, &file.kicks))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PieceSet {
+            name: file.name,
+            pieces,
+        })
+    }
+
+    fn parse_piece(
+        piece: PieceFile,
+        kicks: &HashMap<String, HashMap<String, Vec<[i32; 2]>>>,
+    ) -> Result<Piece, LoadError> {
+        let invalid = |reason: &str| LoadError::Invalid(format!("{}: {}", piece.name, reason));
+
+        if piece.cells.is_empty() || 4 < piece.cells.len() {
+            return Err(invalid("expected one to four orientations"));
+        }
+        if piece.cells[0].is_empty() {
+            return Err(invalid("has no cells"));
+        }
+        if piece
+            .cells
+            .iter()
+            .any(|cells| cells.len() != piece.cells[0].len())
+        {
+            return Err(invalid("orientations differ in the number of cells"));
+        }
+
+        let mut orientations = piece
+            .cells
+            .iter()
+            .map(|cells| cells.iter().map(|[x, y]| (*x, *y)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        while orientations.len() < 4 {
+            let size = piece
+                .size
+                .ok_or_else(|| invalid("needs a size to rotate the missing orientations"))?;
+            let rotated = orientations
+                .last()
+                .unwrap()
+                .iter()
+                .map(|(x, y)| (*y, size - 1 - *x))
+                .collect();
+            orientations.push(rotated);
+        }
+
+        let mut table: [[Vec<(i32, i32)>; 4]; 4] = Default::default();
+        if let Some(name) = piece.kicks.as_ref() {
+            let transitions = kicks
+                .get(name)
+                .ok_or_else(|| invalid(&format!("unknown kick table {}", name)))?;
+
+            for (transition, offsets) in transitions {
+                let (from, to) = transition
+                    .split_once('>')
+                    .and_then(|(from, to)| {
+                        Some((from.parse::<usize>().ok()?, to.parse::<usize>().ok()?))
+                    })
+                    .filter(|(from, to)| *from < 4 && *to < 4)
+                    .ok_or_else(|| invalid(&format!("bad kick transition {}", transition)))?;
+                table[from][to] = offsets.iter().map(|[x, y]| (*x, *y)).collect();
+            }
+        }
+
+        // a turn without kicks only tries the piece where it is
+        for (from, row) in table.iter_mut().enumerate() {
+            for (to, offsets) in row.iter_mut().enumerate() {
+                if from != to && offsets.is_empty() {
+                    offsets.push((0, 0));
+                }
+            }
+        }
+
+        Ok(Piece {
+            color: piece.color,
+            orientations: orientations.try_into().unwrap(),
+            kicks: table,
+            spawn: (piece.spawn[0], piece.spawn[1]),
+        })
+    }
+}
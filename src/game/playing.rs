@@ -1,55 +1,407 @@
-use std::collections::HashSet;
-use std::time::{Duration, Instant};
+use std::collections::{HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
 
 use rand::prelude::*;
+use rand::rngs::Xoshiro256PlusPlus;
 
 use crate::{consts, game};
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateClockwise,
+    RotateCounterClockwise,
+    RotateFlip,
+    Hold,
+}
+
+pub type Bindings = &'static [(winit::keyboard::KeyCode, Action)];
+
+#[rustfmt::skip]
+pub const DEFAULT_BINDINGS: Bindings = &[
+    (winit::keyboard::KeyCode::ArrowLeft,  Action::MoveLeft),
+    (winit::keyboard::KeyCode::ArrowRight, Action::MoveRight),
+    (winit::keyboard::KeyCode::ArrowDown,  Action::SoftDrop),
+    (winit::keyboard::KeyCode::Space,      Action::HardDrop),
+    (winit::keyboard::KeyCode::ArrowUp,    Action::RotateClockwise),
+    (winit::keyboard::KeyCode::KeyX,       Action::RotateClockwise),
+    (winit::keyboard::KeyCode::KeyZ,       Action::RotateCounterClockwise),
+    (winit::keyboard::KeyCode::KeyA,       Action::RotateFlip),
+    (winit::keyboard::KeyCode::KeyC,       Action::Hold),
+    (winit::keyboard::KeyCode::ShiftLeft,  Action::Hold),
+];
+
 #[derive(Clone)]
 pub struct Mino {
     pub x: i32,
     pub y: i32,
+    pub piece: usize,
+    pub orientation: usize,
     pub blocks: Vec<(i32, i32)>,
-    pub template: consts::MinoTemplate,
+    pub color: consts::BlockColor,
+}
+
+// what a game is played with, picked before it starts
+#[derive(Clone)]
+pub struct Config {
+    pub dimensions: consts::Dimensions,
+    pub piece_set: Arc<game::pieces::PieceSet>,
+    pub rotation_system: &'static dyn game::rotation::RotationSystem,
+    pub top_out: TopOutRules,
+    // ARE, the wait between a mino locking and the next one entering
+    pub entry_delay: Duration,
+    // the pause where filled lines stay on the board before they collapse
+    pub line_clear_delay: Duration,
+    // how long a landed mino rests before it locks, restarting whenever it falls a row, or locking
+    // on the next gravity tick when left out
+    pub lock_delay: Option<Duration>,
+    pub hold: bool,
+    // lets the mino be held again and again instead of once per mino
+    pub infinite_hold: bool,
+    // IRS and IHS, where rotating or holding before a mino enters applies to it as it spawns
+    pub initial_rotation: bool,
+    pub initial_hold: bool,
+    pub gravity: Gravity,
+    pub scoring: Scoring,
+    pub randomizer: Randomizer,
+    // how many of the upcoming pieces are shown
+    pub preview: usize,
+    // repeats moves while their key stays down, or one move per press when left out
+    pub auto_shift: Option<AutoShift>,
+    pub hard_drop: bool,
+    pub start_level: u32,
+    // keeps every placement so the game can be exported as a fumen
+    pub replay: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            dimensions: consts::DEFAULT_DIMENSIONS,
+            piece_set: game::pieces::PieceSet::builtin()[0].clone(),
+            rotation_system: game::rotation::SYSTEMS[0],
+            top_out: TopOutRules::default(),
+            entry_delay: consts::DEFAULT_ENTRY_DELAY,
+            line_clear_delay: consts::DEFAULT_LINE_CLEAR_DELAY,
+            lock_delay: None,
+            hold: true,
+            infinite_hold: false,
+            initial_rotation: true,
+            initial_hold: true,
+            gravity: Gravity::Fixed(consts::UPDATE_INTERVAL),
+            scoring: Scoring::Lines,
+            randomizer: Randomizer::Uniform,
+            preview: 0,
+            auto_shift: None,
+            hard_drop: true,
+            start_level: 0,
+            replay: false,
+        }
+    }
+}
+
+impl Config {
+    // the NES ruleset, starting from the level picked on its level select
+    pub fn classic(start_level: u32) -> Self {
+        Self {
+            rotation_system: &game::rotation::Nrs,
+            top_out: TopOutRules {
+                block_out: true,
+                lock_out: false,
+                partial_lock_out: false,
+            },
+            entry_delay: consts::NES_FRAME * consts::NES_ENTRY_DELAY,
+            line_clear_delay: consts::NES_FRAME * consts::NES_LINE_CLEAR_DELAY,
+            hold: false,
+            initial_rotation: false,
+            initial_hold: false,
+            gravity: Gravity::Nes,
+            scoring: Scoring::Nes,
+            randomizer: Randomizer::NesReroll,
+            preview: 1,
+            auto_shift: Some(AutoShift {
+                delay: consts::NES_FRAME * consts::NES_DAS_DELAY,
+                repeat: consts::NES_FRAME * consts::NES_DAS_REPEAT,
+            }),
+            hard_drop: false,
+            start_level,
+            ..Self::default()
+        }
+    }
+
+    // the TGM ruleset, whose speed is set by the master mode as its level goes up
+    pub fn master() -> Self {
+        Self {
+            rotation_system: &game::rotation::Ars,
+            top_out: TopOutRules {
+                block_out: true,
+                lock_out: false,
+                partial_lock_out: false,
+            },
+            hold: false,
+            initial_hold: false,
+            preview: 1,
+            ..Self::default()
+        }
+    }
+
+    // the narrowest board every piece of the set spawns inside of, for the rotation system played
+    pub fn min_width(&self) -> i32 {
+        (consts::MIN_BOARD_WIDTH..)
+            .find(|width| {
+                let spawn_x = consts::Dimensions::new(*width, 0).spawn_x;
+                self.piece_set.pieces.iter().all(|piece| {
+                    let (x, _) = self.rotation_system.spawn(piece);
+                    self.rotation_system
+                        .cells(piece, 0)
+                        .iter()
+                        .all(|(col, _)| (0..*width).contains(&(spawn_x + x + col)))
+                })
+            })
+            .unwrap_or(consts::MIN_BOARD_WIDTH)
+    }
+}
+
+// how long a mino takes to fall one row
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Gravity {
+    Fixed(Duration),
+    // the NES table, speeding up with the level
+    Nes,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scoring {
+    // a point per line
+    Lines,
+    // the NES points per clear, multiplied by the level plus one
+    Nes,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Randomizer {
+    Uniform,
+    // rolls once more on a repeat of the last piece, so repeats are rare but possible
+    NesReroll,
+}
+
+// DAS, the wait before a held move starts repeating and the time between repeats
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AutoShift {
+    pub delay: Duration,
+    pub repeat: Duration,
+}
+
+// the guideline conditions that end a game, each of which a ruleset may leave out
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TopOutRules {
+    // a new piece overlaps the stack where it spawns, or else it is pushed up until it fits
+    pub block_out: bool,
+    // a piece locks entirely above the stack height
+    pub lock_out: bool,
+    // blocks of a piece are left above the stack height once its lines are cleared
+    pub partial_lock_out: bool,
+}
+
+impl Default for TopOutRules {
+    fn default() -> Self {
+        Self {
+            block_out: true,
+            lock_out: true,
+            partial_lock_out: true,
+        }
+    }
+}
+
+// why a game ended, named as the guideline names them
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TopOut {
+    BlockOut,
+    LockOut,
+    PartialLockOut,
+    // garbage pushed the stack or the active mino out of the board
+    GarbageOut,
+}
+
+// what the last mino to lock did
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Placement {
+    pub lines: usize,
+    // a T turned into a spot with three of its four corners filled
+    pub t_spin: bool,
+}
+
+// what happened on the board, for observers to read after each input and update
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Event {
+    Spawned { piece: usize },
+    // the piece put in the hold
+    Held { piece: usize },
+    Locked { piece: usize, placement: Placement },
+    // the filled rows as the mino locked, bottom row 0, before they collapse
+    LinesCleared { rows: Vec<usize> },
+    Scored { score: i32, lines: i32 },
+    LevelChanged { level: u32 },
+    GarbageReceived { lines: i32 },
+    // the garbage rising into the board, received or from the mode itself
+    GarbageInserted { lines: i32 },
+    GarbageSent { lines: i32 },
+    ToppedOut(TopOut),
+}
+
+// where the board is between one mino and the next
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Phase {
+    // a mino is falling, or spawns on the next gravity tick
+    Falling,
+    LineClear {
+        lines: Vec<usize>,
+        remaining_time: Duration,
+    },
+    Entry {
+        remaining_time: Duration,
+    },
+}
+
+// inputs given while no mino is falling, carried over to the next one
+#[derive(Clone, Default, Hash)]
+struct InitialActions {
+    rotation: Option<game::rotation::Direction>,
+    hold: bool,
+}
+
+// garbage rows rising from the bottom on a timer that shortens with every row
+#[derive(Clone)]
+struct RisingGarbage {
+    interval: Duration,
+    remaining_time: Duration,
 }
 
 pub struct GameContext<'a> {
+    pub dimensions: &'a consts::Dimensions,
     pub active_mino: &'a Option<Mino>,
     pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
     pub score: &'a i32,
+    pub lines: &'a i32,
+    // shown only when the gravity goes by level
+    pub level: Option<u32>,
     pub paused: &'a bool,
+    pub pending_garbage: &'a Vec<i32>,
+    // how close the next rising row is, from 0 just after a rise to 1 right before the next
+    pub rising_progress: Option<f32>,
+    // filled lines shown before they collapse
+    pub clearing_lines: &'a [usize],
+    // the held piece in its spawn orientation at the origin
+    pub held_mino: Option<Mino>,
+    // the upcoming pieces in the same way, the next one first
+    pub next_minos: Vec<Mino>,
 }
 
+#[derive(Clone)]
 pub struct GameSystem {
-    rng: ThreadRng,
+    rng: Xoshiro256PlusPlus,
+    bindings: Bindings,
     pressed: HashSet<winit::keyboard::KeyCode>,
-    last_update: Option<Instant>,
+    last_update: Option<Duration>,
     remaining_time: Duration,
+    lock_time: Duration,
 
+    config: Config,
+    phase: Phase,
     active_mino: Option<Mino>,
     blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    initial_actions: InitialActions,
+    held_piece: Option<usize>,
+    hold_used: bool,
+    next_pieces: VecDeque<usize>,
+    last_roll: Option<usize>,
+    // pieces dealt in order before the randomizer takes over
+    sequence: VecDeque<usize>,
+    // whether the active mino last moved by turning, for telling T-spins
+    rotated: bool,
+    // the move key held down and how long it has been charging
+    shift: Option<(Action, Duration)>,
 
     paused: bool,
     score: i32,
+    lines: i32,
+    level: u32,
+    pieces: i32,
+    placements: i32,
+    last_placement: Option<Placement>,
+    // the board before each placement with the mino placed on it
+    replay: Vec<(Vec<Vec<Option<consts::BlockColor>>>, Mino)>,
+    topped_out: Option<TopOut>,
+    // the latest events not yet taken, the oldest ones dropped when nobody reads them
+    events: VecDeque<Event>,
+
+    pending_garbage: Vec<i32>,
+    outgoing_garbage: i32,
+    cleared_garbage: i32,
+    rising_garbage: Option<RisingGarbage>,
 }
 
 impl GameSystem {
     pub fn new() -> Self {
-        Self {
-            rng: rand::rng(),
+        Self::with_bindings(DEFAULT_BINDINGS, rand::random())
+    }
+
+    pub fn with_bindings(bindings: Bindings, seed: u64) -> Self {
+        Self::with_config(bindings, seed, Config::default())
+    }
+
+    pub fn with_config(bindings: Bindings, seed: u64, config: Config) -> Self {
+        let dimensions = config.dimensions;
+        let start_level = config.start_level;
+
+        let mut system = Self {
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+            bindings,
             pressed: HashSet::new(),
             last_update: None,
             remaining_time: Duration::ZERO,
+            lock_time: Duration::ZERO,
 
+            config,
+            phase: Phase::Falling,
             active_mino: None,
-            blocks: vec![
-                vec![None; consts::MAX_BLOCK_WIDTH as usize];
-                consts::MAX_BLOCK_HEIGHT as usize
-            ],
+            blocks: vec![vec![None; dimensions.width as usize]; dimensions.height as usize],
+            initial_actions: InitialActions::default(),
+            held_piece: None,
+            hold_used: false,
+            next_pieces: VecDeque::new(),
+            last_roll: None,
+            sequence: VecDeque::new(),
+            rotated: false,
+            shift: None,
 
             paused: false,
             score: 0,
+            lines: 0,
+            level: start_level,
+            pieces: 0,
+            placements: 0,
+            last_placement: None,
+            replay: vec![],
+            topped_out: None,
+            events: VecDeque::new(),
+
+            pending_garbage: vec![],
+            outgoing_garbage: 0,
+            cleared_garbage: 0,
+            rising_garbage: None,
+        };
+        for _ in 0..system.config.preview {
+            let piece = system.roll_piece();
+            system.next_pieces.push_back(piece);
         }
+        system
     }
 
     pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
@@ -59,97 +411,557 @@ impl GameSystem {
         if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
             match input.state {
                 ElementState::Pressed if !self.pressed.contains(&code) => {
-                    match code {
-                        KeyCode::KeyP => {
-                            self.paused = !self.paused;
-
-                            if !self.paused {
-                                self.last_update = Some(Instant::now());
-                            }
-                        }
-                        KeyCode::Space if !self.paused => {
-                            self.check_and_hard_drop_mino(flow);
-                        }
-                        KeyCode::ArrowUp | KeyCode::KeyX if !self.paused => {
-                            self.check_and_rotate_mino(true);
-                        }
-                        KeyCode::KeyZ if !self.paused => {
-                            self.check_and_rotate_mino(false);
-                        }
-                        KeyCode::ArrowDown if !self.paused => {
-                            self.check_and_move_mino(0, -1);
+                    if code == KeyCode::KeyP {
+                        self.set_paused(!self.paused);
+                    } else if code == KeyCode::F3 {
+                        print!("{}", game::board_text::format(&self.blocks));
+                    } else if code == KeyCode::F2 {
+                        let fumen = game::fumen::encode(&game::fumen::game_pages(self));
+                        if let Err(error) = game::fumen::copy_to_clipboard(&fumen) {
+                            eprintln!("{}", error);
                         }
-                        KeyCode::ArrowRight if !self.paused => {
-                            self.check_and_move_mino(1, 0);
+                    } else if let Some(action) = self.binding(code) {
+                        if matches!(action, Action::MoveLeft | Action::MoveRight) {
+                            self.shift = Some((action, Duration::ZERO));
                         }
-                        KeyCode::ArrowLeft if !self.paused => {
-                            self.check_and_move_mino(-1, 0);
-                        }
-                        _ => {}
+                        self.action(action, flow);
                     }
                     self.pressed.insert(code);
                 }
                 ElementState::Released => {
                     self.pressed.remove(&code);
+
+                    if self
+                        .shift
+                        .is_some_and(|(action, _)| self.binding(code) == Some(action))
+                    {
+                        self.shift = None;
+                    }
                 }
                 _ => {}
             }
         }
     }
 
-    pub fn update(&mut self, flow: &mut game::GameSystemFlow) {
-        let delta_time = self.last_update.map(|last_update| last_update.elapsed());
-        self.last_update = Some(std::time::Instant::now());
+    pub fn binding(&self, code: winit::keyboard::KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(binding, _)| *binding == code)
+            .map(|(_, action)| *action)
+    }
 
+    pub fn action(&mut self, action: Action, flow: &mut game::GameSystemFlow) {
         if self.paused {
             return;
         }
 
+        if self.active_mino.is_none() {
+            match action {
+                Action::RotateClockwise => {
+                    self.initial_actions.rotation = Some(game::rotation::Direction::Clockwise)
+                }
+                Action::RotateCounterClockwise => {
+                    self.initial_actions.rotation =
+                        Some(game::rotation::Direction::CounterClockwise)
+                }
+                Action::RotateFlip => {
+                    self.initial_actions.rotation = Some(game::rotation::Direction::Flip)
+                }
+                Action::Hold => self.initial_actions.hold = true,
+                _ => {}
+            }
+            return;
+        }
+
+        match action {
+            Action::MoveLeft => self.check_and_move_mino(-1, 0),
+            Action::MoveRight => self.check_and_move_mino(1, 0),
+            // with a lock delay, pressing down on a landed mino locks it right away
+            Action::SoftDrop if self.config.lock_delay.is_some() && self.is_landed() => {
+                self.check_and_place_mino(flow)
+            }
+            Action::SoftDrop => self.check_and_move_mino(0, -1),
+            Action::HardDrop => {
+                if self.config.hard_drop {
+                    self.check_and_hard_drop_mino(flow)
+                }
+            }
+            Action::RotateClockwise => {
+                self.check_and_rotate_mino(game::rotation::Direction::Clockwise)
+            }
+            Action::RotateCounterClockwise => {
+                self.check_and_rotate_mino(game::rotation::Direction::CounterClockwise)
+            }
+            Action::RotateFlip => self.check_and_rotate_mino(game::rotation::Direction::Flip),
+            Action::Hold => self.check_and_hold_mino(flow),
+        }
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+
+        // the time spent paused is left out by counting from the next update
+        if !self.paused {
+            self.last_update = None;
+        }
+    }
+
+    // forgets the keys held down, as their release goes to another state while the board is left
+    pub fn release_keys(&mut self) {
+        self.pressed.clear();
+        self.shift = None;
+    }
+
+    pub fn receive_garbage(&mut self, lines: i32) {
+        if 0 < lines {
+            self.pending_garbage.push(lines);
+            self.emit(Event::GarbageReceived { lines });
+        }
+    }
+
+    pub fn take_outgoing_garbage(&mut self) -> i32 {
+        std::mem::take(&mut self.outgoing_garbage)
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.events.drain(..).collect()
+    }
+
+    fn emit(&mut self, event: Event) {
+        if consts::MAX_PENDING_EVENTS <= self.events.len() {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    // inserts garbage right away, lifting the active mino along with the board and topping out
+    // when either no longer fits
+    pub fn insert_garbage(
+        &mut self,
+        lines: i32,
+        placement: game::garbage::HolePlacement,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let overflowed = game::garbage::insert(&mut self.blocks, lines, placement, &mut self.rng);
+        if 0 < lines {
+            self.emit(Event::GarbageInserted { lines });
+        }
+
+        if let Some(active_mino) = self.active_mino.as_mut() {
+            active_mino.y += lines;
+        }
+        if let Phase::LineClear {
+            lines: clearing_lines,
+            ..
+        } = &mut self.phase
+        {
+            let height = self.config.dimensions.height as usize;
+            clearing_lines
+                .iter_mut()
+                .for_each(|line| *line += lines as usize);
+            clearing_lines.retain(|line| *line < height);
+        }
+        let is_blocked = self
+            .active_mino
+            .as_ref()
+            .is_some_and(|active_mino| !self.is_valid_mino(active_mino));
+
+        let is_over_stack_height =
+            self.config.top_out.partial_lock_out && self.is_over_stack_height();
+
+        if overflowed || is_blocked || is_over_stack_height {
+            self.top_out(TopOut::GarbageOut, flow);
+        }
+    }
+
+    pub fn dimensions(&self) -> consts::Dimensions {
+        self.config.dimensions
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn topped_out(&self) -> Option<TopOut> {
+        self.topped_out
+    }
+
+    pub fn set_rising_garbage(&mut self, interval: Duration) {
+        self.rising_garbage = Some(RisingGarbage {
+            interval,
+            remaining_time: interval,
+        });
+    }
+
+    pub fn garbage_lines(&self) -> i32 {
+        self.blocks
+            .iter()
+            .filter(|line| game::garbage::is_garbage_line(line))
+            .count() as i32
+    }
+
+    pub fn cleared_garbage(&self) -> i32 {
+        self.cleared_garbage
+    }
+
+    // the number of minos that have entered the board
+    pub fn pieces(&self) -> i32 {
+        self.pieces
+    }
+
+    // the number of minos that have locked, and what the last one did
+    pub fn placements(&self) -> i32 {
+        self.placements
+    }
+
+    pub fn last_placement(&self) -> Option<Placement> {
+        self.last_placement
+    }
+
+    pub fn replay(&self) -> &[(Vec<Vec<Option<consts::BlockColor>>>, Mino)] {
+        &self.replay
+    }
+
+    // replaces the board, given bottom row first, before the game starts
+    pub fn set_blocks(&mut self, blocks: Vec<Vec<Option<consts::BlockColor>>>) {
+        let width = self.config.dimensions.width as usize;
+        let height = self.config.dimensions.height as usize;
+
+        self.blocks = blocks;
+        self.blocks
+            .iter_mut()
+            .for_each(|line| line.resize(width, None));
+        self.blocks.resize(height, vec![None; width]);
+    }
+
+    // deals these pieces next, the upcoming ones in the preview included
+    pub fn set_sequence(&mut self, pieces: impl IntoIterator<Item = usize>) {
+        self.sequence = pieces.into_iter().collect();
+        self.next_pieces.clear();
+        for _ in 0..self.config.preview {
+            let piece = self.roll_piece();
+            self.next_pieces.push_back(piece);
+        }
+    }
+
+    // puts a piece in the hold before the game starts
+    pub fn set_held_piece(&mut self, piece: Option<usize>) {
+        self.held_piece = piece;
+    }
+
+    pub fn set_infinite_hold(&mut self, infinite_hold: bool) {
+        self.config.infinite_hold = infinite_hold;
+    }
+
+    // replaces the first upcoming piece, when there is a preview to hold it
+    pub fn set_next_piece(&mut self, piece: usize) {
+        if let Some(next_piece) = self.next_pieces.front_mut() {
+            *next_piece = piece;
+        }
+    }
+
+    // goes back to a snapshot of the board, keeping the config, the keys held down, the clock and
+    // the events not yet taken as they are now
+    pub fn restore(&mut self, snapshot: &GameSystem) {
+        let config = self.config.clone();
+        let pressed = std::mem::take(&mut self.pressed);
+        let shift = self.shift;
+        let paused = self.paused;
+        let events = std::mem::take(&mut self.events);
+
+        *self = snapshot.clone();
+        self.config = config;
+        self.pressed = pressed;
+        self.shift = shift;
+        self.paused = paused;
+        self.events = events;
+        self.last_update = None;
+    }
+
+    // changes the speed mid-game, for modes that speed up on their own schedule
+    pub fn set_timing(
+        &mut self,
+        gravity: Gravity,
+        entry_delay: Duration,
+        line_clear_delay: Duration,
+        lock_delay: Option<Duration>,
+        auto_shift: Option<AutoShift>,
+    ) {
+        self.config.gravity = gravity;
+        self.config.entry_delay = entry_delay;
+        self.config.line_clear_delay = line_clear_delay;
+        self.config.lock_delay = lock_delay;
+        self.config.auto_shift = auto_shift;
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
         if let Some(delta_time) = delta_time {
-            self.remaining_time += delta_time;
+            self.step(delta_time, flow);
         }
+    }
 
-        if consts::UPDATE_INTERVAL < self.remaining_time {
-            self.check_and_place_mino(flow);
+    // advances the game by a fixed amount of time, independent of the wall clock
+    pub fn step(&mut self, delta_time: Duration, flow: &mut game::GameSystemFlow) {
+        if self.paused {
+            return;
+        }
+
+        self.check_and_raise_garbage(delta_time, flow);
+        self.check_and_auto_shift(delta_time);
+
+        let interval = self.gravity_interval();
+
+        match &mut self.phase {
+            Phase::Falling => {
+                self.remaining_time += delta_time;
+
+                if let Some(lock_delay) = self.config.lock_delay {
+                    if self.is_landed() {
+                        self.lock_time += delta_time;
+                    }
+                    if lock_delay <= self.lock_time {
+                        self.check_and_place_mino(flow);
+                    }
+                }
+
+                // gravity above one row per tick falls several rows at once, up to 20G
+                while interval < self.remaining_time
+                    && self.phase == Phase::Falling
+                    && self.topped_out.is_none()
+                {
+                    if self.config.lock_delay.is_none() {
+                        self.check_and_place_mino(flow);
+                    }
 
-            self.check_and_move_mino(0, -1);
+                    self.check_and_move_mino(0, -1);
 
-            self.check_and_spawn_mino(flow);
+                    self.check_and_spawn_mino(flow);
 
-            self.remaining_time = self.remaining_time.saturating_sub(consts::UPDATE_INTERVAL);
+                    self.remaining_time = self.remaining_time.saturating_sub(interval);
+                }
+            }
+            Phase::LineClear { remaining_time, .. } => {
+                *remaining_time = remaining_time.saturating_sub(delta_time);
+
+                if remaining_time.is_zero() {
+                    if let Phase::LineClear { lines, .. } =
+                        std::mem::replace(&mut self.phase, Phase::Falling)
+                    {
+                        self.erase_lines(&lines);
+                    }
+                    self.check_and_enter(flow);
+                }
+            }
+            Phase::Entry { remaining_time } => {
+                *remaining_time = remaining_time.saturating_sub(delta_time);
+
+                if remaining_time.is_zero() {
+                    self.phase = Phase::Falling;
+                    self.check_and_spawn_mino(flow);
+                }
+            }
         }
     }
 
+    fn is_free(&self, x: i32, y: i32) -> bool {
+        (0..self.config.dimensions.width).contains(&x)
+            && (0..self.config.dimensions.height).contains(&y)
+            && self.blocks[y as usize][x as usize].is_none()
+    }
+
     fn is_valid_mino(&self, mino: &Mino) -> bool {
-        mino.blocks.iter().all(|(x, y)| {
-            let x = mino.x + x;
-            let y = mino.y + y;
-            (0..consts::MAX_BLOCK_WIDTH).contains(&x)
-                && (0..consts::MAX_BLOCK_HEIGHT).contains(&y)
-                && self.blocks[y as usize][x as usize].is_none()
+        mino.blocks
+            .iter()
+            .all(|(x, y)| self.is_free(mino.x + x, mino.y + y))
+    }
+
+    fn gravity_interval(&self) -> Duration {
+        match self.config.gravity {
+            Gravity::Fixed(interval) => interval,
+            Gravity::Nes => {
+                let frames = consts::NES_GRAVITY
+                    .get(self.level as usize)
+                    .copied()
+                    .unwrap_or(*consts::NES_GRAVITY.last().unwrap());
+                consts::NES_FRAME * frames
+            }
+        }
+    }
+
+    fn roll_piece(&mut self) -> usize {
+        if let Some(piece) = self.sequence.pop_front() {
+            self.last_roll = Some(piece);
+            return piece;
+        }
+
+        let count = self.config.piece_set.pieces.len();
+
+        let piece = match self.config.randomizer {
+            Randomizer::Uniform => self.rng.random_range(0..count),
+            // rolls one past the pieces, and rolls again over the pieces alone on that or on the
+            // last piece
+            Randomizer::NesReroll => {
+                let roll = self.rng.random_range(0..=count);
+                if roll == count || Some(roll) == self.last_roll {
+                    self.rng.random_range(0..count)
+                } else {
+                    roll
+                }
+            }
+        };
+        self.last_roll = Some(piece);
+        piece
+    }
+
+    // whether the falling mino rests on the stack or the floor
+    fn is_landed(&self) -> bool {
+        self.active_mino.as_ref().is_some_and(|active_mino| {
+            let mut next_mino = active_mino.clone();
+            next_mino.y -= 1;
+            !self.is_valid_mino(&next_mino)
         })
     }
 
+    // takes the first upcoming piece and rolls one more to keep the preview full
+    fn next_piece(&mut self) -> usize {
+        let piece = self.roll_piece();
+        self.next_pieces.push_back(piece);
+        self.next_pieces.pop_front().unwrap()
+    }
+
+    fn preview_mino(&self, index: usize) -> Mino {
+        let piece = &self.config.piece_set.pieces[index];
+        Mino {
+            x: 0,
+            y: 0,
+            piece: index,
+            orientation: 0,
+            blocks: self.config.rotation_system.cells(piece, 0),
+            color: piece.color.clone(),
+        }
+    }
+
+    // keys still held down count as given before the mino entered
+    fn held_actions(&self) -> Vec<Action> {
+        self.pressed
+            .iter()
+            .filter_map(|code| self.binding(*code))
+            .collect()
+    }
+
     fn check_and_spawn_mino(&mut self, flow: &mut game::GameSystemFlow) {
-        if self.active_mino.is_none() {
-            let mino_template = consts::MINO_TEMPLATES.choose(&mut self.rng).unwrap();
+        if self.active_mino.is_none() && self.phase == Phase::Falling {
+            let initial_actions = std::mem::take(&mut self.initial_actions);
+            let held_actions = self.held_actions();
+
+            let mut piece = self.next_piece();
+
+            let is_held = initial_actions.hold || held_actions.contains(&Action::Hold);
+            if self.config.hold && self.config.initial_hold && is_held {
+                self.emit(Event::Held { piece });
+                piece = self
+                    .held_piece
+                    .replace(piece)
+                    .unwrap_or_else(|| self.next_piece());
+                self.hold_used = true;
+            }
+
+            let rotation = initial_actions.rotation.or_else(|| {
+                held_actions.iter().find_map(|action| match action {
+                    Action::RotateClockwise => Some(game::rotation::Direction::Clockwise),
+                    Action::RotateCounterClockwise => {
+                        Some(game::rotation::Direction::CounterClockwise)
+                    }
+                    Action::RotateFlip => Some(game::rotation::Direction::Flip),
+                    _ => None,
+                })
+            });
+            let rotation = rotation.filter(|_| self.config.initial_rotation);
+
+            self.pieces += 1;
+            self.spawn_mino(piece, rotation, flow);
+        }
+    }
 
-            let active_mino = Mino {
-                x: consts::SPAWN_BLOCK_X,
-                y: consts::SPAWN_BLOCK_Y,
-                blocks: mino_template.blocks.to_vec(),
-                template: mino_template.clone(),
+    // puts the piece at the spawn position, turned right away if it fits that way
+    fn spawn_mino(
+        &mut self,
+        index: usize,
+        rotation: Option<game::rotation::Direction>,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let rotation_system = self.config.rotation_system;
+        let piece = &self.config.piece_set.pieces[index];
+        let (spawn_x, spawn_y) = rotation_system.spawn(piece);
+
+        let mut active_mino = Mino {
+            x: self.config.dimensions.spawn_x + spawn_x,
+            y: self.config.dimensions.spawn_y + spawn_y,
+            piece: index,
+            orientation: 0,
+            blocks: rotation_system.cells(piece, 0),
+            color: piece.color.clone(),
+        };
+
+        if let Some(direction) = rotation {
+            let orientation = direction.turn(0);
+            let rotated_mino = Mino {
+                orientation,
+                blocks: rotation_system.cells(piece, orientation),
+                ..active_mino.clone()
             };
+            if self.is_valid_mino(&rotated_mino) {
+                active_mino = rotated_mino;
+            }
+        }
 
-            if !self.is_valid_mino(&active_mino) {
-                let state = game::GameSystem::End(game::end::GameSystem::new(
-                    self.blocks.clone(),
-                    self.score,
-                ));
-                *flow = game::GameSystemFlow::To(state);
+        if !self.is_valid_mino(&active_mino) && !self.config.top_out.block_out {
+            let lifted = (1..self.config.dimensions.height)
+                .map(|lift| Mino {
+                    y: active_mino.y + lift,
+                    ..active_mino.clone()
+                })
+                .find(|mino| self.is_valid_mino(mino));
+            if let Some(lifted) = lifted {
+                active_mino = lifted;
             }
+        }
+
+        self.emit(Event::Spawned { piece: index });
 
-            self.active_mino = Some(active_mino);
+        if !self.is_valid_mino(&active_mino) {
+            self.top_out(TopOut::BlockOut, flow);
+        }
+
+        self.active_mino = Some(active_mino);
+        self.lock_time = Duration::ZERO;
+        self.rotated = false;
+    }
+
+    // swaps the mino for the held piece, or the next one when nothing is held, once per mino
+    fn check_and_hold_mino(&mut self, flow: &mut game::GameSystemFlow) {
+        if !self.config.hold || (self.hold_used && !self.config.infinite_hold) {
+            return;
+        }
+
+        if let Some(active_mino) = self.active_mino.take() {
+            self.emit(Event::Held {
+                piece: active_mino.piece,
+            });
+            let piece = self
+                .held_piece
+                .replace(active_mino.piece)
+                .unwrap_or_else(|| self.next_piece());
+            self.hold_used = true;
+            self.remaining_time = Duration::ZERO;
+
+            self.spawn_mino(piece, None, flow);
         }
     }
 
@@ -160,33 +972,104 @@ impl GameSystem {
             next_mino.y -= 1;
 
             if self.is_valid_mino(active_mino) && !self.is_valid_mino(&next_mino) {
+                let stack_height = self.config.dimensions.stack_height;
+                let is_locked_out = active_mino
+                    .blocks
+                    .iter()
+                    .all(|(_, y)| stack_height <= active_mino.y + y);
+                let t_spin = self.rotated && self.is_t_spin(active_mino);
+                let piece = active_mino.piece;
+                if self.config.replay {
+                    self.replay.push((self.blocks.clone(), active_mino.clone()));
+                }
+
                 for (x, y) in active_mino.blocks.iter() {
                     let x = active_mino.x + *x;
                     let y = active_mino.y + *y;
-                    self.blocks[y as usize][x as usize] = Some(active_mino.template.color.clone());
+                    self.blocks[y as usize][x as usize] = Some(active_mino.color.clone());
                 }
                 self.active_mino = None;
+                self.hold_used = false;
+                self.remaining_time = Duration::ZERO;
 
-                self.check_and_erase_blocks();
+                let (score, lines, level) = (self.score, self.lines, self.level);
+                let filled_lines = self.check_and_count_lines();
+                self.placements += 1;
+                let placement = Placement {
+                    lines: filled_lines.len(),
+                    t_spin,
+                };
+                self.last_placement = Some(placement);
 
-                let is_over_stack_height = self
-                    .blocks
-                    .iter()
-                    .skip(consts::MAX_STACK_HEIGHT as usize)
-                    .any(|line| line.iter().any(|block| block.is_some()));
-                if is_over_stack_height {
-                    let state = game::GameSystem::End(game::end::GameSystem::new(
-                        self.blocks.clone(),
-                        self.score,
-                    ));
-                    *flow = game::GameSystemFlow::To(state);
+                self.emit(Event::Locked { piece, placement });
+                if !filled_lines.is_empty() {
+                    self.emit(Event::LinesCleared {
+                        rows: filled_lines.clone(),
+                    });
+                }
+                if (score, lines) != (self.score, self.lines) {
+                    self.emit(Event::Scored {
+                        score: self.score,
+                        lines: self.lines,
+                    });
+                }
+                if level != self.level {
+                    self.emit(Event::LevelChanged { level: self.level });
                 }
 
-                self.remaining_time = Duration::ZERO;
+                let overflowed = self.check_and_exchange_garbage(filled_lines.len());
+
+                if overflowed {
+                    self.top_out(TopOut::GarbageOut, flow);
+                } else if self.config.top_out.lock_out && is_locked_out {
+                    self.top_out(TopOut::LockOut, flow);
+                } else if filled_lines.is_empty() || self.config.line_clear_delay.is_zero() {
+                    self.erase_lines(&filled_lines);
+                    self.check_and_enter(flow);
+                } else {
+                    self.phase = Phase::LineClear {
+                        lines: filled_lines,
+                        remaining_time: self.config.line_clear_delay,
+                    };
+                }
             }
         }
     }
 
+    // a T whose center has at least three of its four diagonal corners filled or out of the board
+    fn is_t_spin(&self, mino: &Mino) -> bool {
+        let piece = &self.config.piece_set.pieces[mino.piece];
+        if game::rotation::Tetromino::of(piece) != Some(game::rotation::Tetromino::T) {
+            return false;
+        }
+
+        let center = mino.blocks.iter().find(|(x, y)| {
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .filter(|(dx, dy)| mino.blocks.contains(&(x + dx, y + dy)))
+                .count()
+                == 3
+        });
+        center.is_some_and(|(x, y)| {
+            [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+                .iter()
+                .filter(|(dx, dy)| !self.is_free(mino.x + x + dx, mino.y + y + dy))
+                .count()
+                >= 3
+        })
+    }
+
+    // once the board has settled, either tops out or waits for the next mino to enter
+    fn check_and_enter(&mut self, flow: &mut game::GameSystemFlow) {
+        if self.config.top_out.partial_lock_out && self.is_over_stack_height() {
+            self.top_out(TopOut::PartialLockOut, flow);
+        } else {
+            self.phase = Phase::Entry {
+                remaining_time: self.config.entry_delay,
+            };
+        }
+    }
+
     fn check_and_move_mino(&mut self, delta_x: i32, delta_y: i32) {
         if let Some(active_mino) = self.active_mino.as_ref() {
             let mut next_mino = active_mino.clone();
@@ -196,32 +1079,50 @@ impl GameSystem {
 
             if self.is_valid_mino(&next_mino) {
                 self.active_mino = Some(next_mino);
+                self.rotated = false;
+
+                if delta_y < 0 {
+                    self.lock_time = Duration::ZERO;
+                }
             }
         }
     }
 
-    fn check_and_rotate_mino(&mut self, clockwise: bool) {
+    // turns the mino to the next orientation and tries each kick of the rotation system until one
+    // fits
+    fn check_and_rotate_mino(&mut self, direction: game::rotation::Direction) {
         if let Some(active_mino) = self.active_mino.as_ref() {
-            let mut next_mino = active_mino.clone();
+            let rotation_system = self.config.rotation_system;
+            let piece = &self.config.piece_set.pieces[active_mino.piece];
 
-            for (x, y) in next_mino.blocks.iter_mut() {
-                let (origin_x, origin_y) = active_mino.template.rotation_origin;
+            let from = active_mino.orientation;
+            let to = direction.turn(from);
+            let blocks = rotation_system.cells(piece, to);
 
-                let shift_x = *x as f32 - origin_x;
-                let shift_y = *y as f32 - origin_y;
+            let may_kick = rotation_system.may_kick(piece, to, &|(x, y)| {
+                !self.is_free(active_mino.x + x, active_mino.y + y)
+            });
+            let kicks = rotation_system.kicks(piece, from, direction);
+            let kicks = if may_kick {
+                &kicks[..]
+            } else {
+                &kicks[..kicks.len().min(1)]
+            };
 
-                let (rotated_x, rotated_y) = if clockwise {
-                    (shift_y, -shift_x)
-                } else {
-                    (-shift_y, shift_x)
+            for (kick_x, kick_y) in kicks.iter() {
+                let next_mino = Mino {
+                    x: active_mino.x + kick_x,
+                    y: active_mino.y + kick_y,
+                    orientation: to,
+                    blocks: blocks.clone(),
+                    ..active_mino.clone()
                 };
 
-                *x = (origin_x + rotated_x).round() as i32;
-                *y = (origin_y + rotated_y).round() as i32;
-            }
-
-            if self.is_valid_mino(&next_mino) {
-                self.active_mino = Some(next_mino);
+                if self.is_valid_mino(&next_mino) {
+                    self.active_mino = Some(next_mino);
+                    self.rotated = true;
+                    return;
+                }
             }
         }
     }
@@ -247,20 +1148,155 @@ impl GameSystem {
         }
     }
 
-    fn check_and_erase_blocks(&mut self) {
-        let mut row_nums = vec![];
+    // charges while a move key stays down, including between minos, and repeats the move once
+    // charged
+    fn check_and_auto_shift(&mut self, delta_time: Duration) {
+        let Some(auto_shift) = self.config.auto_shift else {
+            return;
+        };
+
+        if let Some((action, charge)) = self.shift.as_mut() {
+            let delta_x = if *action == Action::MoveLeft { -1 } else { 1 };
+
+            *charge += delta_time;
+
+            let mut moves = 0;
+            while auto_shift.delay <= *charge {
+                *charge -= auto_shift.repeat.max(Duration::from_millis(1));
+                moves += 1;
+            }
+            for _ in 0..moves {
+                self.check_and_move_mino(delta_x, 0);
+            }
+        }
+    }
+
+    fn check_and_raise_garbage(&mut self, delta_time: Duration, flow: &mut game::GameSystemFlow) {
+        if let Some(rising_garbage) = self.rising_garbage.as_mut() {
+            rising_garbage.remaining_time =
+                rising_garbage.remaining_time.saturating_sub(delta_time);
+
+            if rising_garbage.remaining_time.is_zero() {
+                rising_garbage.interval = rising_garbage
+                    .interval
+                    .mul_f32(consts::SURVIVAL_SPEEDUP)
+                    .max(consts::SURVIVAL_MIN_INTERVAL);
+                rising_garbage.remaining_time = rising_garbage.interval;
+
+                let placement = game::garbage::HolePlacement::Messiness(consts::SURVIVAL_MESSINESS);
+                self.insert_garbage(1, placement, flow);
+            }
+        }
+    }
+
+    fn is_over_stack_height(&self) -> bool {
+        self.blocks
+            .iter()
+            .skip(self.config.dimensions.stack_height as usize)
+            .any(|line| line.iter().any(|block| block.is_some()))
+    }
+
+    fn top_out(&mut self, top_out: TopOut, flow: &mut game::GameSystemFlow) {
+        self.topped_out = Some(top_out);
+        self.emit(Event::ToppedOut(top_out));
+
+        let state = game::GameSystem::End(game::end::GameSystem::new(
+            self.blocks.clone(),
+            self.config.clone(),
+            self.score,
+            top_out,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    // returns whether the received garbage pushed blocks out of the top of the board
+    fn check_and_exchange_garbage(&mut self, erased_lines: usize) -> bool {
+        let attack = consts::ATTACK_TABLE
+            .get(erased_lines)
+            .copied()
+            .unwrap_or(*consts::ATTACK_TABLE.last().unwrap());
+
+        if 0 < erased_lines {
+            let mut remaining = attack;
+            while 0 < remaining && !self.pending_garbage.is_empty() {
+                let canceled = remaining.min(self.pending_garbage[0]);
+                self.pending_garbage[0] -= canceled;
+                remaining -= canceled;
+
+                if self.pending_garbage[0] == 0 {
+                    self.pending_garbage.remove(0);
+                }
+            }
+            self.outgoing_garbage += remaining;
+            if 0 < remaining {
+                self.emit(Event::GarbageSent { lines: remaining });
+            }
+            false
+        } else {
+            let mut overflowed = false;
+            let mut budget = consts::MAX_GARBAGE_PER_PLACE;
+            while 0 < budget && !self.pending_garbage.is_empty() {
+                let lines = budget.min(self.pending_garbage[0]);
+                self.pending_garbage[0] -= lines;
+                budget -= lines;
+
+                overflowed |= game::garbage::insert(
+                    &mut self.blocks,
+                    lines,
+                    game::garbage::HolePlacement::Clean,
+                    &mut self.rng,
+                );
+                self.emit(Event::GarbageInserted { lines });
+
+                if self.pending_garbage[0] == 0 {
+                    self.pending_garbage.remove(0);
+                }
+            }
+            overflowed
+        }
+    }
+
+    // scores the filled lines and returns them, leaving them on the board
+    fn check_and_count_lines(&mut self) -> Vec<usize> {
+        let filled_lines = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.iter().all(|block| block.is_some()))
+            .map(|(row, _)| row)
+            .collect::<Vec<_>>();
 
-        for (row, line) in self.blocks.iter_mut().enumerate() {
-            let is_filled_line = line.iter().all(|block| block.is_some());
-            if is_filled_line {
-                line.iter_mut().for_each(|block| *block = None);
-                row_nums.push(row);
+        for row in filled_lines.iter() {
+            if game::garbage::is_garbage_line(&self.blocks[*row]) {
+                self.cleared_garbage += 1;
+            }
+        }
 
-                self.score += 1;
+        let cleared = filled_lines.len();
+        self.score += match self.config.scoring {
+            Scoring::Lines => cleared as i32,
+            Scoring::Nes => {
+                let points = consts::NES_LINE_SCORES
+                    .get(cleared)
+                    .copied()
+                    .unwrap_or(*consts::NES_LINE_SCORES.last().unwrap());
+                points * (self.level as i32 + 1)
             }
+        };
+        self.lines += cleared as i32;
+        self.level = self
+            .level
+            .max(nes_level(self.config.start_level, self.lines));
+
+        filled_lines
+    }
+
+    fn erase_lines(&mut self, row_nums: &[usize]) {
+        for row in row_nums.iter() {
+            self.blocks[*row].iter_mut().for_each(|block| *block = None);
         }
 
-        for row in 0..consts::MAX_BLOCK_HEIGHT as usize {
+        for row in 0..self.config.dimensions.height as usize {
             let down = row_nums
                 .iter()
                 .filter(|erased_row| **erased_row < row)
@@ -269,12 +1305,370 @@ impl GameSystem {
         }
     }
 
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        self.blocks.hash(&mut hasher);
+        if let Some(active_mino) = self.active_mino.as_ref() {
+            (active_mino.x, active_mino.y, &active_mino.blocks).hash(&mut hasher);
+        }
+        self.remaining_time.hash(&mut hasher);
+        self.lock_time.hash(&mut hasher);
+        self.score.hash(&mut hasher);
+        self.pending_garbage.hash(&mut hasher);
+        self.outgoing_garbage.hash(&mut hasher);
+        self.cleared_garbage.hash(&mut hasher);
+        self.phase.hash(&mut hasher);
+        self.initial_actions.hash(&mut hasher);
+        self.held_piece.hash(&mut hasher);
+        self.hold_used.hash(&mut hasher);
+        self.next_pieces.hash(&mut hasher);
+        self.last_roll.hash(&mut hasher);
+        self.lines.hash(&mut hasher);
+        self.level.hash(&mut hasher);
+        self.pieces.hash(&mut hasher);
+        self.sequence.hash(&mut hasher);
+        self.rotated.hash(&mut hasher);
+        self.placements.hash(&mut hasher);
+        self.last_placement.hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn context(&'_ self) -> GameContext<'_> {
         GameContext {
+            dimensions: &self.config.dimensions,
             blocks: &self.blocks,
             active_mino: &self.active_mino,
             score: &self.score,
+            lines: &self.lines,
+            level: (self.config.gravity == Gravity::Nes).then_some(self.level),
             paused: &self.paused,
+            pending_garbage: &self.pending_garbage,
+            rising_progress: self.rising_garbage.as_ref().map(|rising_garbage| {
+                1.0 - rising_garbage.remaining_time.as_secs_f32()
+                    / rising_garbage.interval.as_secs_f32()
+            }),
+            clearing_lines: match &self.phase {
+                Phase::LineClear { lines, .. } => lines,
+                _ => &[],
+            },
+            held_mino: self.held_piece.map(|index| self.preview_mino(index)),
+            next_minos: self
+                .next_pieces
+                .iter()
+                .map(|index| self.preview_mino(*index))
+                .collect(),
+        }
+    }
+}
+
+// the NES levels up first after ten lines per level started from, or later from high starts, and
+// every ten lines after that
+fn nes_level(start_level: u32, lines: i32) -> u32 {
+    let start = start_level as i32;
+    let first = (start * 10 + 10).min((start * 10 - 50).max(100));
+
+    if lines < first {
+        start_level
+    } else {
+        start_level + 1 + ((lines - first) / 10) as u32
+    }
+}
+
+// FNV-1a, which unlike DefaultHasher hashes alike on every build and platform so peers agree on
+// checksums, integers going in as little endian and sizes as 64 bits
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
         }
     }
+
+    fn write_u16(&mut self, n: u16) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write(&n.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i16(&mut self, n: i16) {
+        self.write_u16(n as u16);
+    }
+
+    fn write_i32(&mut self, n: i32) {
+        self.write_u32(n as u32);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i128(&mut self, n: i128) {
+        self.write_u128(n as u128);
+    }
+
+    fn write_isize(&mut self, n: isize) {
+        self.write_u64(n as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::harness::{Harness, State};
+
+    const EMPTY: &str = "..........";
+
+    #[test]
+    fn hard_drop_lands_on_the_floor() {
+        let mut harness = Harness::new(EMPTY, "IO", 0);
+        harness.run("drop");
+
+        assert_eq!(harness.board(), "...IIII...\n");
+        assert_eq!(harness.score(), 0);
+        assert_eq!(harness.state(), State::Entry);
+
+        harness.run("next left*4 drop");
+        assert_eq!(harness.board(), "OO........\nOO.IIII...\n");
+        assert_eq!(
+            harness.states,
+            [
+                State::Entry,
+                State::Falling,
+                State::Entry,
+                State::Falling,
+                State::Entry
+            ]
+        );
+    }
+
+    #[test]
+    fn clears_lines_with_gaps_between() {
+        let mut harness = Harness::new(
+            "
+            T.........
+            GGGGGGGG.G
+            GGGG.GGG.G
+            GGGGGGGG.G
+            ",
+            "I",
+            0,
+        );
+        harness.run("cw right*3 drop");
+
+        assert_eq!(harness.state(), State::LineClear);
+        assert_eq!(harness.lines(), 2);
+        assert_eq!(harness.score(), 2);
+
+        harness.run("next");
+        assert_eq!(harness.board(), "T.......I.\nGGGG.GGGIG\n");
+        assert_eq!(
+            harness.states,
+            [
+                State::Entry,
+                State::Falling,
+                State::LineClear,
+                State::Entry,
+                State::Falling
+            ]
+        );
+    }
+
+    #[test]
+    fn clears_at_once_without_a_delay() {
+        let config = Config {
+            line_clear_delay: Duration::ZERO,
+            ..Config::default()
+        };
+        let mut harness = Harness::with_config(
+            "
+            GGGGGGGG.G
+            GGGGGGGG.G
+            GGG.GGGG.G
+            GGGGGGGG.G
+            ",
+            "I",
+            0,
+            config,
+        );
+        harness.run("cw right*3 drop");
+
+        assert_eq!(harness.board(), "GGG.GGGGIG\n");
+        assert_eq!(harness.lines(), 3);
+        assert!(!harness.states.contains(&State::LineClear));
+    }
+
+    #[test]
+    fn rotations_kick_off_the_walls() {
+        let mut harness = Harness::new(EMPTY, "II", 0);
+        harness.run("cw left*5 cw drop");
+        assert_eq!(harness.board(), "IIII......\n");
+
+        harness.run("next ccw right*5 ccw drop");
+        assert_eq!(harness.board(), "IIII..IIII\n");
+    }
+
+    #[test]
+    fn moves_stop_at_the_walls() {
+        let mut harness = Harness::new(EMPTY, "T", 0);
+        harness.run("left*9 drop");
+        assert_eq!(harness.board(), ".T........\nTTT.......\n");
+    }
+
+    #[test]
+    fn locking_above_the_stack_tops_out() {
+        let board = "GGGGGGGGG.\n".repeat(20);
+        let mut harness = Harness::new(&board, "O", 0);
+        harness.run("drop");
+
+        assert_eq!(harness.state(), State::ToppedOut(TopOut::LockOut));
+        assert!(harness.ended);
+    }
+
+    #[test]
+    fn spawning_into_the_stack_tops_out() {
+        let board = "GGGGGGGGG.\n".repeat(22);
+        let mut harness = Harness::new(&board, "T", 0);
+
+        assert_eq!(harness.state(), State::ToppedOut(TopOut::BlockOut));
+        assert_eq!(
+            harness.states,
+            [State::Entry, State::ToppedOut(TopOut::BlockOut)]
+        );
+        assert!(harness.ended);
+        assert_eq!(
+            harness.system.take_events().last(),
+            Some(&Event::ToppedOut(TopOut::BlockOut))
+        );
+    }
+
+    #[test]
+    fn emits_events_as_they_happen() {
+        let piece = |letter| game::puzzles::tetromino_index(letter).unwrap();
+        let mut harness = Harness::new(
+            "
+            GGGGGGGG.G
+            GGGG.GGG.G
+            GGGGGGGG.G
+            ",
+            "OI",
+            0,
+        );
+        harness.run("hold cw right*3 drop");
+
+        assert_eq!(
+            harness.system.take_events(),
+            [
+                Event::Spawned { piece: piece('O') },
+                Event::Held { piece: piece('O') },
+                Event::Spawned { piece: piece('I') },
+                Event::Locked {
+                    piece: piece('I'),
+                    placement: Placement {
+                        lines: 2,
+                        t_spin: false
+                    }
+                },
+                Event::LinesCleared { rows: vec![0, 2] },
+                Event::Scored { score: 2, lines: 2 },
+                Event::GarbageSent { lines: 1 },
+            ]
+        );
+        assert!(harness.system.take_events().is_empty());
+    }
+
+    #[test]
+    fn keeps_only_the_latest_events() {
+        let mut harness = Harness::new(EMPTY, "", 0);
+        for _ in 0..consts::MAX_PENDING_EVENTS {
+            harness.system.receive_garbage(1);
+        }
+        harness.run("drop");
+
+        let events = harness.system.take_events();
+        assert_eq!(events.len(), consts::MAX_PENDING_EVENTS);
+        assert_eq!(events[0], Event::GarbageReceived { lines: 1 });
+        assert_eq!(events.last(), Some(&Event::GarbageInserted { lines: 1 }));
+    }
+
+    #[test]
+    fn time_spent_paused_is_left_out() {
+        let mut harness = Harness::new(EMPTY, "T", 0);
+        let y = harness.system.context().active_mino.as_ref().unwrap().y;
+
+        harness.system.set_paused(true);
+        harness.step(Duration::from_secs(10));
+        harness.system.set_paused(false);
+        harness.step(Duration::from_secs(10));
+        harness.run("tick");
+        assert_eq!(harness.system.context().active_mino.as_ref().unwrap().y, y);
+
+        harness.step(consts::UPDATE_INTERVAL);
+        assert_eq!(
+            harness.system.context().active_mino.as_ref().unwrap().y,
+            y - 1
+        );
+    }
+
+    #[test]
+    fn same_seed_and_script_play_the_same() {
+        let script = "drop next cw right drop next hold left*2 drop next flip drop tick*30";
+        let mut first = Harness::new(EMPTY, "", 7);
+        let mut second = Harness::new(EMPTY, "", 7);
+        first.run(script);
+        second.run(script);
+
+        assert_eq!(first.board(), second.board());
+        assert_eq!(first.system.checksum(), second.system.checksum());
+    }
+
+    #[test]
+    fn checksums_hash_with_fnv_1a() {
+        let mut hasher = Fnv1a::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        let mut sized = Fnv1a::default();
+        sized.write_usize(7);
+        let mut fixed = Fnv1a::default();
+        fixed.write_u64(7);
+        assert_eq!(sized.finish(), fixed.finish());
+    }
+
+    #[test]
+    fn wider_pieces_need_a_wider_board() {
+        let tetromino = Config::default();
+        assert_eq!(tetromino.min_width(), consts::MIN_BOARD_WIDTH);
+
+        let pentomino = Config {
+            piece_set: game::pieces::PieceSet::find("pentomino").unwrap(),
+            ..Config::default()
+        };
+        assert_eq!(pentomino.min_width(), 6);
+    }
 }
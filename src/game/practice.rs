@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use crate::game;
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub undo: usize,
+    pub redo: usize,
+    pub infinite_hold: bool,
+}
+
+// drills openers on a board that never ends, where every placement can be taken back
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    board: game::playing::GameSystem,
+    // the board as the current mino entered, the state undo goes back from
+    snapshot: game::playing::GameSystem,
+    undo: Vec<game::playing::GameSystem>,
+    redo: Vec<game::playing::GameSystem>,
+    saved: game::playing::GameSystem,
+    seen_pieces: i32,
+}
+
+impl GameSystem {
+    pub fn new(config: game::playing::Config) -> Self {
+        // picking the next piece by hand needs it in the preview
+        let config = game::playing::Config {
+            preview: config.preview.max(1),
+            ..config
+        };
+        let board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            rand::random(),
+            config,
+        );
+
+        Self {
+            pressed: HashSet::new(),
+
+            snapshot: board.clone(),
+            undo: vec![],
+            redo: vec![],
+            saved: board.clone(),
+            seen_pieces: board.pieces(),
+            board,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            if input.state == ElementState::Pressed && !self.pressed.contains(&code) {
+                match code {
+                    KeyCode::Escape => {
+                        let state = game::GameSystem::Start(game::start::GameSystem::new());
+                        *flow = game::GameSystemFlow::To(state);
+                    }
+                    KeyCode::KeyU => {
+                        if let Some(snapshot) = self.undo.pop() {
+                            self.redo
+                                .push(std::mem::replace(&mut self.snapshot, snapshot));
+                            self.restore_snapshot();
+                        }
+                    }
+                    KeyCode::KeyY => {
+                        if let Some(snapshot) = self.redo.pop() {
+                            self.undo
+                                .push(std::mem::replace(&mut self.snapshot, snapshot));
+                            self.restore_snapshot();
+                        }
+                    }
+                    KeyCode::KeyQ | KeyCode::KeyW => {
+                        let count = self.board.config().piece_set.pieces.len();
+                        let next_piece = self
+                            .board
+                            .context()
+                            .next_minos
+                            .first()
+                            .map_or(0, |next_mino| next_mino.piece);
+                        self.board.set_next_piece(if code == KeyCode::KeyQ {
+                            (next_piece + count - 1) % count
+                        } else {
+                            (next_piece + 1) % count
+                        });
+                    }
+                    KeyCode::KeyI => {
+                        let infinite_hold = !self.board.config().infinite_hold;
+                        self.board.set_infinite_hold(infinite_hold);
+                    }
+                    KeyCode::KeyS => self.saved = self.board.clone(),
+                    KeyCode::KeyR => {
+                        self.undo.push(self.snapshot.clone());
+                        self.redo.clear();
+                        self.snapshot = self.saved.clone();
+                        self.restore_snapshot();
+                    }
+                    _ => {}
+                }
+            }
+            match input.state {
+                ElementState::Pressed => {
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+            }
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_snapshot(board_flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_snapshot(board_flow);
+    }
+
+    fn restore_snapshot(&mut self) {
+        self.board.restore(&self.snapshot);
+        self.seen_pieces = self.board.pieces();
+    }
+
+    // pushes the board onto the undo stack whenever a new mino enters, and takes back the
+    // placement that topped it out instead of ending the game
+    fn check_and_snapshot(&mut self, board_flow: game::GameSystemFlow) {
+        if let game::GameSystemFlow::To(_) = board_flow {
+            self.restore_snapshot();
+        } else if self.seen_pieces != self.board.pieces() {
+            let snapshot = std::mem::replace(&mut self.snapshot, self.board.clone());
+            // the empty board before the first mino is not a placement to go back to
+            if self.seen_pieces != 0 {
+                self.undo.push(snapshot);
+                self.redo.clear();
+            }
+            self.seen_pieces = self.board.pieces();
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            undo: self.undo.len(),
+            redo: self.redo.len(),
+            infinite_hold: self.board.config().infinite_hold,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::game;
+use crate::game::puzzle_select::Selection;
+use crate::game::puzzles::Verdict;
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub name: &'a str,
+    pub goal: game::puzzles::Goal,
+    pub pieces_left: usize,
+}
+
+// plays one puzzle from its board and pieces until it is solved or failed
+pub struct GameSystem {
+    board: game::playing::GameSystem,
+
+    selection: Selection,
+}
+
+impl GameSystem {
+    pub fn new(selection: Selection) -> Self {
+        let puzzle = selection.puzzle();
+        let mut board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            rand::random(),
+            puzzle.config(),
+        );
+        board.set_blocks(puzzle.blocks.clone());
+        board.set_sequence(puzzle.sequence.iter().copied());
+
+        Self { board, selection }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::{KeyCode, PhysicalKey};
+
+        if input.state == ElementState::Pressed {
+            match input.physical_key {
+                PhysicalKey::Code(KeyCode::Escape) => {
+                    let state = game::GameSystem::PuzzleSelect(
+                        game::puzzle_select::GameSystem::with_selection(self.selection.clone()),
+                    );
+                    *flow = game::GameSystemFlow::To(state);
+                    return;
+                }
+                PhysicalKey::Code(KeyCode::KeyR) => {
+                    *self = Self::new(self.selection.clone());
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let placements = self.board.placements();
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_judge(placements, board_flow, flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let placements = self.board.placements();
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_judge(placements, board_flow, flow);
+    }
+
+    // judges the board after every placement, where topping out fails the puzzle
+    fn check_and_judge(
+        &mut self,
+        placements: i32,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let verdict = if let game::GameSystemFlow::To(_) = board_flow {
+            Some(Verdict::Failed)
+        } else if placements != self.board.placements() {
+            self.selection.puzzle().judge(&self.board)
+        } else {
+            None
+        };
+
+        if let Some(verdict) = verdict {
+            if verdict == Verdict::Solved {
+                let mut progress = game::puzzles::Progress::load();
+                progress.set_solved(&self.selection.pack().name, &self.selection.puzzle().name);
+                progress.save();
+            }
+
+            let state = game::GameSystem::PuzzleEnd(game::puzzle_end::GameSystem::new(
+                self.board.context().blocks.clone(),
+                self.board.config().clone(),
+                self.selection.clone(),
+                verdict,
+            ));
+            *flow = game::GameSystemFlow::To(state);
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        let puzzle = self.selection.puzzle();
+
+        GameContext {
+            board: self.board.context(),
+            name: &puzzle.name,
+            goal: puzzle.goal,
+            pieces_left: puzzle
+                .piece_limit
+                .saturating_sub(self.board.placements() as usize),
+        }
+    }
+}
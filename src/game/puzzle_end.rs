@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use crate::game::puzzle_select::Selection;
+use crate::game::puzzles::Verdict;
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
+    pub name: &'a str,
+    pub verdict: &'a Verdict,
+    pub has_next: bool,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    config: game::playing::Config,
+    selection: Selection,
+    verdict: Verdict,
+}
+
+impl GameSystem {
+    pub fn new(
+        blocks: Vec<Vec<Option<consts::BlockColor>>>,
+        config: game::playing::Config,
+        selection: Selection,
+        verdict: Verdict,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            blocks,
+            config,
+            selection,
+            verdict,
+        }
+    }
+
+    // a solved puzzle moves on to the next one in the pack, a failed one is tried again
+    fn next_selection(&self) -> Option<Selection> {
+        match self.verdict {
+            Verdict::Solved => self.selection.next(),
+            Verdict::Failed => Some(self.selection.clone()),
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Enter => {
+                            if let Some(selection) = self.next_selection() {
+                                let state = game::GameSystem::Puzzle(
+                                    game::puzzle::GameSystem::new(selection),
+                                );
+                                *flow = game::GameSystemFlow::To(state);
+                            }
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::PuzzleSelect(
+                                game::puzzle_select::GameSystem::with_selection(
+                                    self.selection.clone(),
+                                ),
+                            );
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            blocks: &self.blocks,
+            dimensions: &self.config.dimensions,
+            name: &self.selection.puzzle().name,
+            verdict: &self.verdict,
+            has_next: self.next_selection().is_some(),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::game;
+use crate::game::menu::{Choice, Item, Menu};
+use crate::game::puzzles::{Puzzle, PuzzlePack};
+
+pub struct GameContext<'a> {
+    pub menu: &'a Menu,
+}
+
+// the puzzle picked from the packs, carried through a puzzle and its result back to the list
+#[derive(Clone)]
+pub struct Selection {
+    pub packs: Vec<Arc<PuzzlePack>>,
+    pub pack: usize,
+    pub puzzle: usize,
+}
+
+impl Selection {
+    pub fn pack(&self) -> &PuzzlePack {
+        &self.packs[self.pack]
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.pack().puzzles[self.puzzle]
+    }
+
+    // the puzzle after this one in the same pack
+    pub fn next(&self) -> Option<Selection> {
+        (self.puzzle + 1 < self.pack().puzzles.len()).then(|| Selection {
+            puzzle: self.puzzle + 1,
+            ..self.clone()
+        })
+    }
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    selection: Selection,
+    progress: game::puzzles::Progress,
+    // the puzzles of the pack, solved ones marked with a star
+    menu: Menu,
+}
+
+impl GameSystem {
+    pub fn new() -> Self {
+        Self::with_selection(Selection {
+            packs: PuzzlePack::builtin().to_vec(),
+            pack: 0,
+            puzzle: 0,
+        })
+    }
+
+    // a pack read from a file comes first, ahead of the built-in ones
+    pub fn with_pack(pack: PuzzlePack) -> Self {
+        let mut packs = vec![Arc::new(pack)];
+        packs.extend(PuzzlePack::builtin().iter().cloned());
+
+        Self::with_selection(Selection {
+            packs,
+            pack: 0,
+            puzzle: 0,
+        })
+    }
+
+    pub fn with_selection(selection: Selection) -> Self {
+        let mut system = Self {
+            pressed: HashSet::new(),
+
+            selection,
+            progress: game::puzzles::Progress::load(),
+            menu: Menu::new("", vec![]),
+        };
+        system.refresh_menu();
+        system
+    }
+
+    fn refresh_menu(&mut self) {
+        let pack = self.selection.pack();
+        let items = pack
+            .puzzles
+            .iter()
+            .map(|puzzle| {
+                let solved = self.progress.is_solved(&pack.name, &puzzle.name);
+                Item::new(format!(
+                    "{}{}",
+                    puzzle.name.to_uppercase(),
+                    if solved { " *" } else { "" }
+                ))
+            })
+            .collect();
+
+        self.menu = Menu::new(pack.name.to_uppercase(), items);
+        self.menu.select(self.selection.puzzle);
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    if let Some(choice) = self.menu.key(code) {
+                        self.choose(choice, flow);
+                    }
+                    self.selection.puzzle = self.menu.selected;
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn mouse_input(&mut self, input: &game::MouseInput, flow: &mut game::GameSystemFlow) {
+        if let Some(choice) = self.menu.mouse_input(input) {
+            self.choose(choice, flow);
+        }
+        self.selection.puzzle = self.menu.selected;
+    }
+
+    // left and right on any puzzle switch the pack
+    fn choose(&mut self, choice: Choice, flow: &mut game::GameSystemFlow) {
+        let packs = self.selection.packs.len();
+
+        match choice {
+            Choice::Chosen(puzzle) => {
+                let selection = Selection {
+                    puzzle,
+                    ..self.selection.clone()
+                };
+                let state = game::GameSystem::Puzzle(game::puzzle::GameSystem::new(selection));
+                *flow = game::GameSystemFlow::To(state);
+            }
+            Choice::Back => {
+                let state = game::GameSystem::Start(game::start::GameSystem::new());
+                *flow = game::GameSystemFlow::To(state);
+            }
+            Choice::Previous(_) | Choice::Next(_) => {
+                self.selection.pack = if let Choice::Previous(_) = choice {
+                    (self.selection.pack + packs - 1) % packs
+                } else {
+                    (self.selection.pack + 1) % packs
+                };
+                self.selection.puzzle = 0;
+                self.refresh_menu();
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext { menu: &self.menu }
+    }
+}
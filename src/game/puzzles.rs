@@ -0,0 +1,255 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, OnceLock};
+
+use crate::{consts, game};
+
+// the puzzle packs shipped with the game
+const BUILTIN_SOURCES: &[&str] = &[
+    include_str!("../../assets/puzzles/basics.toml"),
+    include_str!("../../assets/puzzles/spins.toml"),
+];
+
+#[derive(Debug)]
+pub struct PuzzlePack {
+    pub name: String,
+    pub puzzles: Vec<Puzzle>,
+}
+
+#[derive(Debug)]
+pub struct Puzzle {
+    pub name: String,
+    // the starting board, bottom row first
+    pub blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    pub width: i32,
+    // indices into the tetromino set, dealt in order
+    pub sequence: Vec<usize>,
+    pub goal: Goal,
+    // how many pieces may be placed before the puzzle is failed
+    pub piece_limit: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Goal {
+    Lines(i32),
+    PerfectClear,
+    TSpinTriple,
+    // checked once the last piece is placed
+    NoHoles,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "failed to read puzzle pack: {}", error),
+            LoadError::Parse(error) => write!(f, "failed to parse puzzle pack: {}", error),
+            LoadError::Invalid(reason) => write!(f, "invalid puzzle pack: {}", reason),
+        }
+    }
+}
+
+// the layout of a puzzle pack file, see assets/puzzles
+#[derive(serde::Deserialize)]
+struct PuzzlePackFile {
+    name: String,
+    puzzles: Vec<PuzzleFile>,
+}
+
+#[derive(serde::Deserialize)]
+struct PuzzleFile {
+    name: String,
+    goal: Goal,
+    pieces: String,
+    limit: Option<usize>,
+    // rows from the top down in the plain text board format, see game::board_text
+    board: Vec<String>,
+}
+
+impl PuzzlePack {
+    pub fn builtin() -> &'static [Arc<PuzzlePack>] {
+        static BUILTIN: OnceLock<Vec<Arc<PuzzlePack>>> = OnceLock::new();
+
+        BUILTIN.get_or_init(|| {
+            BUILTIN_SOURCES
+                .iter()
+                .map(|source| Arc::new(Self::parse(source).expect("invalid built-in puzzle pack")))
+                .collect()
+        })
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<PuzzlePack, LoadError> {
+        let source = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+        Self::parse(&source)
+    }
+
+    pub fn parse(source: &str) -> Result<PuzzlePack, LoadError> {
+        let file = toml::from_str::<PuzzlePackFile>(source).map_err(LoadError::Parse)?;
+
+        if file.puzzles.is_empty() {
+            return Err(LoadError::Invalid(format!("{} has no puzzles", file.name)));
+        }
+
+        let puzzles = file
+            .puzzles
+            .into_iter()
+            .map(Self::parse_puzzle)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PuzzlePack {
+            name: file.name,
+            puzzles,
+        })
+    }
+
+    fn parse_puzzle(puzzle: PuzzleFile) -> Result<Puzzle, LoadError> {
+        let invalid = |reason: &str| LoadError::Invalid(format!("{}: {}", puzzle.name, reason));
+
+        let blocks = game::board_text::parse(&puzzle.board.join("\n"))
+            .map_err(|error| invalid(&error.to_string()))?;
+        let width = blocks.first().map_or(0, Vec::len);
+        if width < consts::MIN_BOARD_WIDTH as usize {
+            return Err(invalid("board is too narrow"));
+        }
+
+        let sequence = puzzle
+            .pieces
+            .chars()
+            .map(tetromino_index)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid("unknown piece in the sequence"))?;
+        if sequence.is_empty() {
+            return Err(invalid("has no pieces"));
+        }
+
+        Ok(Puzzle {
+            name: puzzle.name,
+            blocks,
+            width: width as i32,
+            piece_limit: puzzle.limit.unwrap_or(sequence.len()),
+            sequence,
+            goal: puzzle.goal,
+        })
+    }
+}
+
+impl Puzzle {
+    // the rules a puzzle is played with, on the tetromino set and a board as wide as its own
+    pub fn config(&self) -> game::playing::Config {
+        game::playing::Config {
+            dimensions: consts::Dimensions::new(
+                self.width,
+                consts::DEFAULT_DIMENSIONS.visible_height,
+            ),
+            piece_set: game::pieces::PieceSet::builtin()[0].clone(),
+            // lines go at once so every placement can be judged on the board it leaves
+            line_clear_delay: std::time::Duration::ZERO,
+            preview: consts::PUZZLE_PREVIEW,
+            ..Default::default()
+        }
+    }
+}
+
+// how a puzzle turned out
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Solved,
+    Failed,
+}
+
+impl Puzzle {
+    // judges the board after each placement, until the goal is met or the pieces run out
+    pub fn judge(&self, board: &game::playing::GameSystem) -> Option<Verdict> {
+        let placement = board.last_placement()?;
+        let cx = board.context();
+        let is_last = self.piece_limit <= board.placements() as usize;
+
+        let is_met = match self.goal {
+            Goal::Lines(lines) => lines <= *cx.lines,
+            Goal::PerfectClear => {
+                0 < placement.lines && cx.blocks.iter().flatten().all(Option::is_none)
+            }
+            Goal::TSpinTriple => placement.t_spin && placement.lines == 3,
+            Goal::NoHoles => is_last && !has_holes(cx.blocks),
+        };
+
+        if is_met {
+            Some(Verdict::Solved)
+        } else if is_last {
+            Some(Verdict::Failed)
+        } else {
+            None
+        }
+    }
+}
+
+// an empty cell with a block somewhere above it in the same column
+fn has_holes(blocks: &[Vec<Option<consts::BlockColor>>]) -> bool {
+    let width = blocks.first().map_or(0, Vec::len);
+    (0..width).any(|col| {
+        let top = blocks.iter().rposition(|line| line[col].is_some());
+        top.is_some_and(|top| blocks[..top].iter().any(|line| line[col].is_none()))
+    })
+}
+
+// where the tetromino of that letter is in the built-in tetromino set
+pub fn tetromino_index(letter: char) -> Option<usize> {
+    use game::rotation::Tetromino;
+
+    let tetromino = match letter {
+        'I' => Tetromino::I,
+        'O' => Tetromino::O,
+        'S' => Tetromino::S,
+        'Z' => Tetromino::Z,
+        'J' => Tetromino::J,
+        'L' => Tetromino::L,
+        'T' => Tetromino::T,
+        _ => return None,
+    };
+    game::pieces::PieceSet::builtin()[0]
+        .pieces
+        .iter()
+        .position(|piece| Tetromino::of(piece) == Some(tetromino))
+}
+
+// the puzzles solved so far, each named by its pack and its own name
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Progress {
+    solved: BTreeSet<String>,
+}
+
+impl Progress {
+    // starts afresh when there is no progress saved yet or it cannot be read
+    pub fn load() -> Self {
+        std::fs::read_to_string(consts::PUZZLE_PROGRESS_PATH)
+            .ok()
+            .and_then(|source| toml::from_str(&source).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = toml::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|source| {
+                std::fs::write(consts::PUZZLE_PROGRESS_PATH, source)
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("failed to save puzzle progress: {}", error);
+        }
+    }
+
+    pub fn is_solved(&self, pack: &str, puzzle: &str) -> bool {
+        self.solved.contains(&format!("{}/{}", pack, puzzle))
+    }
+
+    pub fn set_solved(&mut self, pack: &str, puzzle: &str) {
+        self.solved.insert(format!("{}/{}", pack, puzzle));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{consts, game};
+
+// the best scores and the last games played, kept between runs
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Records {
+    // best first
+    pub high_scores: Vec<HighScore>,
+    // newest first
+    pub replays: Vec<Replay>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct HighScore {
+    pub score: i32,
+    pub lines: i32,
+    pub pieces: String,
+    pub rotation: String,
+}
+
+// a game as a multi-page fumen, one page per placement
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Replay {
+    pub score: i32,
+    pub lines: i32,
+    pub fumen: String,
+}
+
+impl Records {
+    // starts afresh when there are no records saved yet or they cannot be read
+    pub fn load() -> Self {
+        std::fs::read_to_string(consts::RECORDS_PATH)
+            .ok()
+            .and_then(|source| toml::from_str(&source).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = toml::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|source| {
+                std::fs::write(consts::RECORDS_PATH, source).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("failed to save records: {}", error);
+        }
+    }
+
+    // keeps the score when it makes the table and the game as the newest replay
+    pub fn add(&mut self, board: &game::playing::GameSystem) {
+        let cx = board.context();
+        let config = board.config();
+
+        self.high_scores.push(HighScore {
+            score: *cx.score,
+            lines: *cx.lines,
+            pieces: config.piece_set.name.clone(),
+            rotation: config.rotation_system.name().to_string(),
+        });
+        // a stable sort ranks an earlier score above a later one that ties it
+        self.high_scores
+            .sort_by_key(|high_score| std::cmp::Reverse(high_score.score));
+        self.high_scores.truncate(consts::MAX_HIGH_SCORES);
+
+        self.replays.insert(
+            0,
+            Replay {
+                score: *cx.score,
+                lines: *cx.lines,
+                fumen: game::fumen::encode(&game::fumen::game_pages(board)),
+            },
+        );
+        self.replays.truncate(consts::MAX_SAVED_REPLAYS);
+    }
+}
@@ -0,0 +1,499 @@
+use crate::game::pieces::Piece;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+    Flip,
+}
+
+impl Direction {
+    pub fn turn(self, orientation: usize) -> usize {
+        match self {
+            Direction::Clockwise => (orientation + 1) % 4,
+            Direction::CounterClockwise => (orientation + 3) % 4,
+            Direction::Flip => (orientation + 2) % 4,
+        }
+    }
+}
+
+// the seven tetrominoes, which rotation systems other than the piece set's own tables know about
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tetromino {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+impl Tetromino {
+    const SHAPES: &[(Tetromino, [(i32, i32); 4])] = &[
+        (Tetromino::I, [(0, 0), (1, 0), (2, 0), (3, 0)]),
+        (Tetromino::O, [(0, 0), (0, 1), (1, 0), (1, 1)]),
+        (Tetromino::T, [(0, 0), (1, 0), (1, 1), (2, 0)]),
+        (Tetromino::S, [(0, 0), (1, 0), (1, 1), (2, 1)]),
+        (Tetromino::Z, [(0, 1), (1, 0), (1, 1), (2, 0)]),
+        (Tetromino::J, [(0, 0), (0, 1), (1, 0), (2, 0)]),
+        (Tetromino::L, [(0, 0), (1, 0), (2, 0), (2, 1)]),
+    ];
+
+    pub fn letter(self) -> char {
+        match self {
+            Tetromino::I => 'I',
+            Tetromino::O => 'O',
+            Tetromino::T => 'T',
+            Tetromino::S => 'S',
+            Tetromino::Z => 'Z',
+            Tetromino::J => 'J',
+            Tetromino::L => 'L',
+        }
+    }
+
+    // the tetromino a piece is shaped like in its spawn orientation, whatever the set calls it
+    pub fn of(piece: &Piece) -> Option<Tetromino> {
+        let cells = &piece.orientations[0];
+        let min_x = cells.iter().map(|(x, _)| *x).min()?;
+        let min_y = cells.iter().map(|(_, y)| *y).min()?;
+
+        let mut shape = cells
+            .iter()
+            .map(|(x, y)| (x - min_x, y - min_y))
+            .collect::<Vec<_>>();
+        shape.sort();
+
+        Self::SHAPES
+            .iter()
+            .find(|(_, cells)| shape == cells)
+            .map(|(tetromino, _)| *tetromino)
+    }
+}
+
+// decides where the cells of a piece are in each orientation, where it spawns and which offsets
+// are tried in order when it turns, orientation 0 being the spawn orientation
+pub trait RotationSystem: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn cells(&self, piece: &Piece, orientation: usize) -> Vec<(i32, i32)> {
+        piece.orientations[orientation].clone()
+    }
+
+    // the offset from the spawn position of the board
+    fn spawn(&self, piece: &Piece) -> (i32, i32) {
+        piece.spawn
+    }
+
+    fn kicks(&self, piece: &Piece, from: usize, direction: Direction) -> Vec<(i32, i32)>;
+
+    // whether the kicks after the first may be tried, given which cells of the turned piece are
+    // blocked where it stands
+    fn may_kick(&self, _piece: &Piece, _to: usize, _blocked: &dyn Fn((i32, i32)) -> bool) -> bool {
+        true
+    }
+}
+
+pub const SYSTEMS: &[&dyn RotationSystem] = &[&Srs, &SrsPlus, &Ars, &Nrs];
+
+pub fn find(name: &str) -> Option<&'static dyn RotationSystem> {
+    SYSTEMS
+        .iter()
+        .find(|system| system.name().eq_ignore_ascii_case(name))
+        .copied()
+}
+
+// spawns bottom aligned at the spawn row, as the classic systems do
+fn bottom_spawn(system: &dyn RotationSystem, piece: &Piece) -> (i32, i32) {
+    let min_y = system
+        .cells(piece, 0)
+        .iter()
+        .map(|(_, y)| *y)
+        .min()
+        .unwrap_or(0);
+    (piece.spawn.0, -min_y)
+}
+
+// the guideline Super Rotation System, read from the kick tables of the piece set
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn name(&self) -> &'static str {
+        "SRS"
+    }
+
+    fn kicks(&self, piece: &Piece, from: usize, direction: Direction) -> Vec<(i32, i32)> {
+        piece.kicks[from][direction.turn(from)].clone()
+    }
+}
+
+// SRS with the symmetric I kicks and the 180 kicks of TETR.IO
+pub struct SrsPlus;
+
+#[rustfmt::skip]
+const SRS_PLUS_FLIP_KICKS: [&[(i32, i32)]; 4] = [
+    &[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],
+    &[(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],
+    &[(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)],
+    &[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],
+];
+
+#[rustfmt::skip]
+const SRS_PLUS_I_KICKS: [[&[(i32, i32)]; 4]; 4] = [
+    [&[], &[(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)], &[], &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]],
+    [&[(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)], &[], &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], &[]],
+    [&[], &[(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)], &[], &[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]],
+    [&[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], &[], &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], &[]],
+];
+
+impl RotationSystem for SrsPlus {
+    fn name(&self) -> &'static str {
+        "SRS+"
+    }
+
+    fn kicks(&self, piece: &Piece, from: usize, direction: Direction) -> Vec<(i32, i32)> {
+        let to = direction.turn(from);
+        match (direction, Tetromino::of(piece)) {
+            (Direction::Flip, _) => SRS_PLUS_FLIP_KICKS[from].to_vec(),
+            (_, Some(Tetromino::I)) => SRS_PLUS_I_KICKS[from][to].to_vec(),
+            _ => piece.kicks[from][to].clone(),
+        }
+    }
+}
+
+// the cells of a tetromino in each of its orientations
+type Orientations = [[(i32, i32); 4]; 4];
+
+// the Arika Rotation System of TGM, where pieces spawn flat side down, sit on the bottom of
+// their box and kick one column right, then left, except for I
+pub struct Ars;
+
+#[rustfmt::skip]
+const ARS_CELLS: &[(Tetromino, Orientations)] = &[
+    (Tetromino::I, [
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 0), (2, 1), (2, 2), (2, 3)],
+    ]),
+    (Tetromino::T, [
+        [(0, 1), (1, 1), (2, 1), (1, 0)],
+        [(1, 2), (0, 1), (1, 1), (1, 0)],
+        [(1, 1), (0, 0), (1, 0), (2, 0)],
+        [(1, 2), (1, 1), (2, 1), (1, 0)],
+    ]),
+    (Tetromino::S, [
+        [(1, 1), (2, 1), (0, 0), (1, 0)],
+        [(0, 2), (0, 1), (1, 1), (1, 0)],
+        [(1, 1), (2, 1), (0, 0), (1, 0)],
+        [(0, 2), (0, 1), (1, 1), (1, 0)],
+    ]),
+    (Tetromino::Z, [
+        [(0, 1), (1, 1), (1, 0), (2, 0)],
+        [(2, 2), (2, 1), (1, 1), (1, 0)],
+        [(0, 1), (1, 1), (1, 0), (2, 0)],
+        [(2, 2), (2, 1), (1, 1), (1, 0)],
+    ]),
+    (Tetromino::J, [
+        [(0, 1), (1, 1), (2, 1), (2, 0)],
+        [(1, 2), (1, 1), (0, 0), (1, 0)],
+        [(0, 1), (0, 0), (1, 0), (2, 0)],
+        [(1, 2), (2, 2), (1, 1), (1, 0)],
+    ]),
+    (Tetromino::L, [
+        [(0, 1), (1, 1), (2, 1), (0, 0)],
+        [(0, 2), (1, 2), (1, 1), (1, 0)],
+        [(2, 1), (0, 0), (1, 0), (2, 0)],
+        [(1, 2), (1, 1), (1, 0), (2, 0)],
+    ]),
+];
+
+impl RotationSystem for Ars {
+    fn name(&self) -> &'static str {
+        "ARS"
+    }
+
+    fn cells(&self, piece: &Piece, orientation: usize) -> Vec<(i32, i32)> {
+        let tetromino = Tetromino::of(piece);
+        match ARS_CELLS.iter().find(|(t, _)| Some(*t) == tetromino) {
+            Some((_, cells)) => cells[orientation].to_vec(),
+            None => piece.orientations[orientation].clone(),
+        }
+    }
+
+    fn spawn(&self, piece: &Piece) -> (i32, i32) {
+        bottom_spawn(self, piece)
+    }
+
+    fn kicks(&self, piece: &Piece, _from: usize, _direction: Direction) -> Vec<(i32, i32)> {
+        match Tetromino::of(piece) {
+            Some(Tetromino::I | Tetromino::O) => vec![(0, 0)],
+            _ => vec![(0, 0), (1, 0), (-1, 0)],
+        }
+    }
+
+    // the center column rule: J, L and T don't kick when the first blocked cell of the turned
+    // piece, reading its box from the top left, is in the middle column
+    fn may_kick(&self, piece: &Piece, to: usize, blocked: &dyn Fn((i32, i32)) -> bool) -> bool {
+        if !matches!(
+            Tetromino::of(piece),
+            Some(Tetromino::J | Tetromino::L | Tetromino::T)
+        ) {
+            return true;
+        }
+
+        let mut cells = self.cells(piece, to);
+        cells.sort_by_key(|(x, y)| (-y, *x));
+        cells
+            .into_iter()
+            .find(|cell| blocked(*cell))
+            .is_none_or(|(x, _)| x != 1)
+    }
+}
+
+// the Nintendo Rotation System of the NES, which never kicks and turns around a fixed cell, with
+// vertical S, Z and I leaning right
+pub struct Nrs;
+
+#[rustfmt::skip]
+const NRS_CELLS: &[(Tetromino, Orientations)] = &[
+    (Tetromino::I, [
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 4), (2, 3), (2, 2), (2, 1)],
+        [(0, 2), (1, 2), (2, 2), (3, 2)],
+        [(2, 4), (2, 3), (2, 2), (2, 1)],
+    ]),
+    (Tetromino::T, [
+        [(0, 1), (1, 1), (2, 1), (1, 0)],
+        [(1, 2), (0, 1), (1, 1), (1, 0)],
+        [(0, 1), (1, 1), (2, 1), (1, 2)],
+        [(1, 2), (1, 1), (2, 1), (1, 0)],
+    ]),
+    (Tetromino::S, [
+        [(1, 1), (2, 1), (0, 0), (1, 0)],
+        [(1, 2), (1, 1), (2, 1), (2, 0)],
+        [(1, 1), (2, 1), (0, 0), (1, 0)],
+        [(1, 2), (1, 1), (2, 1), (2, 0)],
+    ]),
+    (Tetromino::Z, [
+        [(0, 1), (1, 1), (1, 0), (2, 0)],
+        [(2, 2), (1, 1), (2, 1), (1, 0)],
+        [(0, 1), (1, 1), (1, 0), (2, 0)],
+        [(2, 2), (1, 1), (2, 1), (1, 0)],
+    ]),
+    (Tetromino::J, [
+        [(0, 1), (1, 1), (2, 1), (2, 0)],
+        [(1, 2), (1, 1), (0, 0), (1, 0)],
+        [(0, 2), (0, 1), (1, 1), (2, 1)],
+        [(1, 2), (2, 2), (1, 1), (1, 0)],
+    ]),
+    (Tetromino::L, [
+        [(0, 1), (1, 1), (2, 1), (0, 0)],
+        [(0, 2), (1, 2), (1, 1), (1, 0)],
+        [(2, 2), (0, 1), (1, 1), (2, 1)],
+        [(1, 2), (1, 1), (1, 0), (2, 0)],
+    ]),
+];
+
+impl RotationSystem for Nrs {
+    fn name(&self) -> &'static str {
+        "NRS"
+    }
+
+    fn cells(&self, piece: &Piece, orientation: usize) -> Vec<(i32, i32)> {
+        let tetromino = Tetromino::of(piece);
+        match NRS_CELLS.iter().find(|(t, _)| Some(*t) == tetromino) {
+            Some((_, cells)) => cells[orientation].to_vec(),
+            None => piece.orientations[orientation].clone(),
+        }
+    }
+
+    fn spawn(&self, piece: &Piece) -> (i32, i32) {
+        bottom_spawn(self, piece)
+    }
+
+    fn kicks(&self, _piece: &Piece, _from: usize, _direction: Direction) -> Vec<(i32, i32)> {
+        vec![(0, 0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::pieces::PieceSet;
+
+    type Offsets = &'static [(i32, i32)];
+    type Cells = [(i32, i32); 4];
+
+    const CW: Direction = Direction::Clockwise;
+    const CCW: Direction = Direction::CounterClockwise;
+    const FLIP: Direction = Direction::Flip;
+
+    fn tetromino(tetromino: Tetromino) -> &'static Piece {
+        PieceSet::builtin()[0]
+            .pieces
+            .iter()
+            .find(|piece| Tetromino::of(piece) == Some(tetromino))
+            .unwrap()
+    }
+
+    fn sorted(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn recognises_tetrominoes_in_every_built_in_set() {
+        for piece_set in PieceSet::builtin() {
+            let count = piece_set
+                .pieces
+                .iter()
+                .filter(|piece| Tetromino::of(piece).is_some())
+                .count();
+            let expected = match piece_set.name.as_str() {
+                "Tetromino" | "Polyomino" => 7,
+                "Beginner" => 3,
+                _ => 0,
+            };
+            assert_eq!(count, expected, "{}", piece_set.name);
+        }
+    }
+
+    #[test]
+    fn finds_systems_by_name() {
+        for name in ["srs", "SRS+", "ars", "Nrs"] {
+            assert!(find(name).is_some(), "{}", name);
+        }
+        assert!(find("dtet").is_none());
+    }
+
+    // (system, piece, from, direction, kicks)
+    #[rustfmt::skip]
+    const KICKS: &[(&str, Tetromino, usize, Direction, Offsets)] = &[
+        ("SRS", Tetromino::T, 0, CW,  &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+        ("SRS", Tetromino::T, 1, CW,  &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+        ("SRS", Tetromino::J, 2, CW,  &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+        ("SRS", Tetromino::L, 3, CW,  &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+        ("SRS", Tetromino::S, 0, CCW, &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+        ("SRS", Tetromino::Z, 2, CCW, &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+        ("SRS", Tetromino::I, 0, CW,  &[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+        ("SRS", Tetromino::I, 1, CW,  &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+        ("SRS", Tetromino::I, 2, CCW, &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+        ("SRS", Tetromino::I, 0, CCW, &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+        ("SRS", Tetromino::T, 0, FLIP, &[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)]),
+        ("SRS", Tetromino::I, 3, FLIP, &[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)]),
+        ("SRS+", Tetromino::T, 0, CW, &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+        ("SRS+", Tetromino::I, 0, CW, &[(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)]),
+        ("SRS+", Tetromino::I, 1, CCW, &[(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]),
+        ("SRS+", Tetromino::I, 2, CW, &[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+        ("SRS+", Tetromino::I, 3, CW, &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+        ("SRS+", Tetromino::I, 1, FLIP, &[(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)]),
+        ("SRS+", Tetromino::S, 2, FLIP, &[(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)]),
+        ("ARS", Tetromino::T, 0, CW,  &[(0, 0), (1, 0), (-1, 0)]),
+        ("ARS", Tetromino::S, 1, CCW, &[(0, 0), (1, 0), (-1, 0)]),
+        ("ARS", Tetromino::I, 0, CW,  &[(0, 0)]),
+        ("ARS", Tetromino::O, 0, CW,  &[(0, 0)]),
+        ("NRS", Tetromino::T, 0, CW,  &[(0, 0)]),
+        ("NRS", Tetromino::I, 1, CCW, &[(0, 0)]),
+        ("NRS", Tetromino::L, 2, FLIP, &[(0, 0)]),
+    ];
+
+    #[test]
+    fn kick_tables_conform() {
+        for (name, piece, from, direction, kicks) in KICKS.iter().copied() {
+            let system = find(name).unwrap();
+            assert_eq!(
+                system.kicks(tetromino(piece), from, direction),
+                kicks,
+                "{} {:?} {} {:?}",
+                name,
+                piece,
+                from,
+                direction
+            );
+        }
+    }
+
+    // (system, piece, orientation, cells)
+    #[rustfmt::skip]
+    const CELLS: &[(&str, Tetromino, usize, Cells)] = &[
+        ("SRS", Tetromino::T, 0, [(0, 1), (1, 1), (1, 2), (2, 1)]),
+        ("SRS", Tetromino::I, 1, [(2, 0), (2, 1), (2, 2), (2, 3)]),
+        ("ARS", Tetromino::T, 0, [(0, 1), (1, 0), (1, 1), (2, 1)]),
+        ("ARS", Tetromino::T, 2, [(0, 0), (1, 0), (1, 1), (2, 0)]),
+        ("ARS", Tetromino::S, 1, [(0, 1), (0, 2), (1, 0), (1, 1)]),
+        ("ARS", Tetromino::Z, 3, [(1, 0), (1, 1), (2, 1), (2, 2)]),
+        ("NRS", Tetromino::T, 2, [(0, 1), (1, 1), (1, 2), (2, 1)]),
+        ("NRS", Tetromino::S, 1, [(1, 1), (1, 2), (2, 0), (2, 1)]),
+        ("NRS", Tetromino::Z, 1, [(1, 0), (1, 1), (2, 1), (2, 2)]),
+        ("NRS", Tetromino::I, 3, [(2, 1), (2, 2), (2, 3), (2, 4)]),
+    ];
+
+    #[test]
+    fn orientations_conform() {
+        for (name, piece, orientation, cells) in CELLS.iter().copied() {
+            let system = find(name).unwrap();
+            assert_eq!(
+                sorted(system.cells(tetromino(piece), orientation)),
+                cells,
+                "{} {:?} {}",
+                name,
+                piece,
+                orientation
+            );
+        }
+    }
+
+    #[test]
+    fn every_orientation_keeps_the_shape() {
+        for system in SYSTEMS {
+            for piece_set in PieceSet::builtin() {
+                for piece in piece_set.pieces.iter() {
+                    for orientation in 0..4 {
+                        assert_eq!(
+                            system.cells(piece, orientation).len(),
+                            piece.orientations[0].len()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // (system, piece, spawn offset)
+    #[rustfmt::skip]
+    const SPAWNS: &[(&str, Tetromino, (i32, i32))] = &[
+        ("SRS", Tetromino::T, (0, -1)),
+        ("SRS", Tetromino::I, (0, -2)),
+        ("SRS", Tetromino::O, (1, 0)),
+        ("ARS", Tetromino::T, (0, 0)),
+        ("ARS", Tetromino::I, (0, -2)),
+        ("NRS", Tetromino::J, (0, 0)),
+        ("NRS", Tetromino::O, (1, 0)),
+    ];
+
+    #[test]
+    fn spawn_states_conform() {
+        for (name, piece, spawn) in SPAWNS.iter().copied() {
+            let system = find(name).unwrap();
+            assert_eq!(
+                system.spawn(tetromino(piece)),
+                spawn,
+                "{} {:?}",
+                name,
+                piece
+            );
+        }
+    }
+
+    #[test]
+    fn ars_center_column_rule() {
+        let t = tetromino(Tetromino::T);
+        // turning into orientation 1 with the middle of the top row taken
+        assert!(!Ars.may_kick(t, 1, &|cell| cell == (1, 2)));
+        // the left nub taken instead
+        assert!(Ars.may_kick(t, 1, &|cell| cell == (0, 1)));
+        // the rule leaves other pieces alone
+        assert!(Ars.may_kick(tetromino(Tetromino::S), 1, &|cell| cell.0 == 1));
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use rand::prelude::*;
+
+use crate::game::bot;
+use crate::{consts, game};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strategy {
+    Random,
+    Attackers,
+    KOs,
+    Badges,
+}
+
+pub const STRATEGIES: &[Strategy] = &[
+    Strategy::Random,
+    Strategy::Attackers,
+    Strategy::KOs,
+    Strategy::Badges,
+];
+
+pub struct Opponent {
+    pub blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    pub active_mino: Option<game::playing::Mino>,
+    pub pending_garbage: i32,
+    pub alive: bool,
+    pub targeted: bool,
+}
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub opponents: &'a Vec<Opponent>,
+    pub strategy: &'a Strategy,
+    pub badges: &'a u32,
+    pub kos: &'a u32,
+    pub remaining: &'a usize,
+    pub paused: &'a bool,
+}
+
+struct Player {
+    alive: bool,
+    strategy: Strategy,
+    targets: Vec<usize>,
+    last_attacker: Option<usize>,
+    badges: u32,
+    kos: u32,
+}
+
+// player 0 is the human, every other player is a bot simulated by the pool
+pub struct GameSystem {
+    rng: StdRng,
+    pressed: HashSet<winit::keyboard::KeyCode>,
+    last_update: Option<Duration>,
+    retarget_time: Duration,
+
+    board: game::playing::GameSystem,
+    pool: bot::Pool,
+    players: Vec<Player>,
+    opponents: Vec<Opponent>,
+    remaining: usize,
+
+    paused: bool,
+}
+
+impl GameSystem {
+    pub fn new(bots: usize) -> Self {
+        let bots = bots.clamp(consts::ROYALE_MIN_BOTS, consts::ROYALE_MAX_BOTS);
+        let mut rng = StdRng::from_rng(&mut rand::rng());
+
+        let pool_bots = (1..=bots)
+            .map(|id| {
+                let think_time = rng.random_range(consts::ROYALE_BOT_THINK_TIME);
+                (id, bot::Bot::new(rng.random(), think_time))
+            })
+            .collect();
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        let players = (0..=bots)
+            .map(|_| Player {
+                alive: true,
+                strategy: *STRATEGIES.choose(&mut rng).unwrap(),
+                targets: vec![],
+                last_attacker: None,
+                badges: 0,
+                kos: 0,
+            })
+            .collect();
+
+        let opponents = (0..bots)
+            .map(|_| Opponent {
+                blocks: vec![
+                    vec![None; consts::DEFAULT_DIMENSIONS.width as usize];
+                    consts::DEFAULT_DIMENSIONS.height as usize
+                ],
+                active_mino: None,
+                pending_garbage: 0,
+                alive: true,
+                targeted: false,
+            })
+            .collect();
+
+        let mut system = Self {
+            rng,
+            pressed: HashSet::new(),
+            last_update: None,
+            retarget_time: Duration::ZERO,
+
+            board: game::playing::GameSystem::new(),
+            pool: bot::Pool::new(pool_bots, threads),
+            players,
+            opponents,
+            remaining: bots + 1,
+
+            paused: false,
+        };
+        system.players[0].strategy = Strategy::Random;
+        system.retarget();
+        system
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    let strategy = match code {
+                        KeyCode::Digit1 => Some(Strategy::Random),
+                        KeyCode::Digit2 => Some(Strategy::Attackers),
+                        KeyCode::Digit3 => Some(Strategy::KOs),
+                        KeyCode::Digit4 => Some(Strategy::Badges),
+                        _ => None,
+                    };
+
+                    if code == KeyCode::KeyP {
+                        self.paused = !self.paused;
+                        self.board.set_paused(self.paused);
+                        self.last_update = None;
+                    } else if let Some(strategy) = strategy {
+                        self.players[0].strategy = strategy;
+                        self.retarget();
+                    } else if let Some(action) = self.board.binding(code) {
+                        let mut board_flow = game::GameSystemFlow::Default;
+                        self.board.action(action, &mut board_flow);
+                        self.check_and_attack(0);
+                        self.check_and_finish(board_flow, flow);
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if self.paused {
+            return;
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_attack(0);
+
+        for report in self.pool.poll() {
+            let opponent = &mut self.opponents[report.id - 1];
+            opponent.blocks = report.blocks;
+            opponent.active_mino = report.active_mino;
+            opponent.pending_garbage = report.pending_garbage;
+
+            if self.players[report.id].alive {
+                self.attack(report.id, report.outgoing_garbage);
+
+                if report.topped_out {
+                    self.eliminate(report.id);
+                }
+            }
+        }
+
+        if let Some(delta_time) = delta_time {
+            self.pool.step(delta_time);
+
+            self.retarget_time += delta_time;
+            if consts::ROYALE_RETARGET_INTERVAL < self.retarget_time {
+                self.retarget_time = Duration::ZERO;
+                self.retarget();
+            }
+        }
+
+        self.check_and_finish(board_flow, flow);
+    }
+
+    fn check_and_attack(&mut self, id: usize) {
+        let lines = self.board.take_outgoing_garbage();
+        self.attack(id, lines);
+    }
+
+    fn attack(&mut self, from: usize, lines: i32) {
+        if lines <= 0 {
+            return;
+        }
+
+        let multiplier = consts::ROYALE_BADGE_MULTIPLIERS
+            .iter()
+            .rev()
+            .find(|(badges, _)| *badges <= self.players[from].badges)
+            .map_or(1.0, |(_, multiplier)| *multiplier);
+        let lines = (lines as f32 * multiplier).floor() as i32;
+
+        for target in self.players[from].targets.clone() {
+            if !self.players[target].alive {
+                continue;
+            }
+
+            self.players[target].last_attacker = Some(from);
+            if target == 0 {
+                self.board.receive_garbage(lines);
+            } else {
+                self.pool.send_garbage(target, lines);
+            }
+        }
+    }
+
+    fn eliminate(&mut self, id: usize) {
+        self.players[id].alive = false;
+        self.remaining -= 1;
+
+        if let Some(opponent) = id.checked_sub(1).and_then(|i| self.opponents.get_mut(i)) {
+            opponent.alive = false;
+        }
+
+        // the last attacker takes the badges of the knocked out player along with its own
+        if let Some(killer) = self.players[id].last_attacker {
+            if self.players[killer].alive {
+                let badges = self.players[id].badges + 1;
+                self.players[killer].badges += badges;
+                self.players[killer].kos += 1;
+            }
+        }
+
+        self.retarget();
+    }
+
+    fn retarget(&mut self) {
+        let alive = (0..self.players.len())
+            .filter(|id| self.players[*id].alive)
+            .collect::<Vec<_>>();
+
+        for id in alive.iter().copied() {
+            let others = alive
+                .iter()
+                .copied()
+                .filter(|other| *other != id)
+                .collect::<Vec<_>>();
+
+            let targets = match self.players[id].strategy {
+                Strategy::Random => others.choose(&mut self.rng).copied().into_iter().collect(),
+                Strategy::Attackers => {
+                    let attackers = others
+                        .iter()
+                        .copied()
+                        .filter(|other| self.players[*other].targets.contains(&id))
+                        .collect::<Vec<_>>();
+                    if attackers.is_empty() {
+                        others.choose(&mut self.rng).copied().into_iter().collect()
+                    } else {
+                        attackers
+                    }
+                }
+                Strategy::KOs => others
+                    .iter()
+                    .copied()
+                    .max_by_key(|other| self.danger(*other))
+                    .into_iter()
+                    .collect(),
+                Strategy::Badges => others
+                    .iter()
+                    .copied()
+                    .max_by_key(|other| self.players[*other].badges)
+                    .into_iter()
+                    .collect(),
+            };
+
+            self.players[id].targets = targets;
+        }
+
+        for (index, opponent) in self.opponents.iter_mut().enumerate() {
+            opponent.targeted = self.players[0].targets.contains(&(index + 1));
+        }
+    }
+
+    // a rough measure of how close a player is to topping out
+    fn danger(&self, id: usize) -> i32 {
+        let (blocks, pending_garbage) = if id == 0 {
+            let cx = self.board.context();
+            (cx.blocks, cx.pending_garbage.iter().sum())
+        } else {
+            let opponent = &self.opponents[id - 1];
+            (&opponent.blocks, opponent.pending_garbage)
+        };
+
+        let height = blocks
+            .iter()
+            .rposition(|line| line.iter().any(|block| block.is_some()))
+            .map_or(0, |row| row as i32 + 1);
+
+        height + pending_garbage
+    }
+
+    fn check_and_finish(
+        &mut self,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let topped_out = matches!(board_flow, game::GameSystemFlow::To(_));
+        if !topped_out && 1 < self.remaining {
+            return;
+        }
+
+        let placement = if topped_out { self.remaining } else { 1 };
+        let cx = self.board.context();
+        let state = game::GameSystem::RoyaleEnd(game::royale_end::GameSystem::new(
+            cx.blocks.clone(),
+            *cx.dimensions,
+            *cx.score,
+            placement,
+            self.players.len(),
+            self.players[0].kos,
+            self.players[0].badges,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            opponents: &self.opponents,
+            strategy: &self.players[0].strategy,
+            badges: &self.players[0].badges,
+            kos: &self.players[0].kos,
+            remaining: &self.remaining,
+            paused: &self.paused,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
+    pub score: &'a i32,
+    pub placement: &'a usize,
+    pub players: &'a usize,
+    pub kos: &'a u32,
+    pub badges: &'a u32,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    dimensions: consts::Dimensions,
+    score: i32,
+    placement: usize,
+    players: usize,
+    kos: u32,
+    badges: u32,
+}
+
+impl GameSystem {
+    pub fn new(
+        blocks: Vec<Vec<Option<consts::BlockColor>>>,
+        dimensions: consts::Dimensions,
+        score: i32,
+        placement: usize,
+        players: usize,
+        kos: u32,
+        badges: u32,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            blocks,
+            dimensions,
+            score,
+            placement,
+            players,
+            kos,
+            badges,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Enter => {
+                            let bots = self.players - 1;
+                            let state =
+                                game::GameSystem::Royale(game::royale::GameSystem::new(bots));
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            blocks: &self.blocks,
+            dimensions: &self.dimensions,
+            score: &self.score,
+            placement: &self.placement,
+            players: &self.players,
+            kos: &self.kos,
+            badges: &self.badges,
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::{consts, game};
+
+// a mode written in Rhai, loaded at runtime so new modes need no recompiling
+//
+// a script defines any of these hooks, each given the game as its first argument:
+//
+//   on_start(game)
+//   on_tick(game, ms)                       every update, with the milliseconds since the last
+//   on_lock(game, piece, lines, t_spin)     the piece as a letter, or "" outside tetrominoes
+//   on_clear(game, rows)                    the filled rows, bottom row 0, before they collapse
+//   on_top_out(game, reason)                "block out", "lock out", "partial lock out" or
+//                                           "garbage out"
+//
+// and the game offers:
+//
+//   game.width, game.height, game.score, game.lines
+//   game.get(x, y) and game.set(x, y, letter)   blocks in the plain text board letters, x from the
+//                                               left and y from the bottom, both from 0, where
+//                                               the falling mino and lines being cleared can't
+//                                               be set
+//   game.garbage(lines)                         raises that many garbage rows
+//   game.gravity(ms)                            the time for the mino to fall a row
+//   game.end(message)                           ends the game with that message
+//   game.hud                                    the text shown over the board
+//   game.vars                                   a map kept between hooks, as hooks can't see the
+//                                               script's globals
+//
+// scripts run in a sandbox without file or network access and with limits on the work a hook
+// may do, so a runaway script fails instead of freezing the game
+
+// what a hook sees of the game and what it asks for, applied once the hook returns
+#[derive(Default)]
+pub struct Requests {
+    pub blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    pub blocks_edited: bool,
+    // cells that can't be set, under the falling mino or on the lines being cleared
+    pub fixed: Vec<(usize, usize)>,
+    pub score: i32,
+    pub lines: i32,
+    pub garbage: i32,
+    pub gravity: Option<Duration>,
+    pub end: Option<String>,
+    pub hud: String,
+    vars: rhai::Map,
+}
+
+#[derive(Clone)]
+struct Game(Rc<RefCell<Requests>>);
+
+impl Game {
+    fn cell(&self, x: i64, y: i64) -> Result<(usize, usize), Box<rhai::EvalAltResult>> {
+        let requests = self.0.borrow();
+        let height = requests.blocks.len() as i64;
+        let width = requests.blocks.first().map_or(0, |line| line.len()) as i64;
+
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            Ok((x as usize, y as usize))
+        } else {
+            Err(format!("{}, {} is outside the board", x, y).into())
+        }
+    }
+}
+
+pub struct Script {
+    engine: rhai::Engine,
+    ast: rhai::AST,
+    game: Game,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        Self::compile(&source)
+    }
+
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(consts::SCRIPT_MAX_OPERATIONS);
+        engine.set_max_call_levels(consts::SCRIPT_MAX_CALL_LEVELS);
+        engine.set_max_expr_depths(consts::SCRIPT_MAX_EXPR_DEPTH, consts::SCRIPT_MAX_EXPR_DEPTH);
+        engine.set_max_string_size(consts::SCRIPT_MAX_SIZE);
+        engine.set_max_array_size(consts::SCRIPT_MAX_SIZE);
+        engine.set_max_map_size(consts::SCRIPT_MAX_SIZE);
+        // scripts can't load other files
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+
+        engine.register_type_with_name::<Game>("Game");
+        engine.register_get("width", |game: &mut Game| {
+            game.0.borrow().blocks.first().map_or(0, |line| line.len()) as i64
+        });
+        engine.register_get("height", |game: &mut Game| {
+            game.0.borrow().blocks.len() as i64
+        });
+        engine.register_get("score", |game: &mut Game| game.0.borrow().score as i64);
+        engine.register_get("lines", |game: &mut Game| game.0.borrow().lines as i64);
+        engine.register_fn("get", |game: &mut Game, x: i64, y: i64| {
+            let (x, y) = game.cell(x, y)?;
+            let letter = game::board_text::block_letter(&game.0.borrow().blocks[y][x]);
+            Ok::<_, Box<rhai::EvalAltResult>>(letter.to_string())
+        });
+        engine.register_fn("set", |game: &mut Game, x: i64, y: i64, letter: &str| {
+            let (x, y) = game.cell(x, y)?;
+            if game.0.borrow().fixed.contains(&(x, y)) {
+                return Err(format!("{}, {} is taken by the falling mino or a clear", x, y).into());
+            }
+            let block = letter
+                .chars()
+                .next()
+                .filter(|_| letter.chars().count() == 1)
+                .and_then(game::board_text::block_color)
+                .ok_or_else(|| format!("unknown block {}", letter))?;
+
+            let mut requests = game.0.borrow_mut();
+            requests.blocks[y][x] = block;
+            requests.blocks_edited = true;
+            Ok::<_, Box<rhai::EvalAltResult>>(())
+        });
+        engine.register_fn("garbage", |game: &mut Game, lines: i64| {
+            game.0.borrow_mut().garbage +=
+                lines.clamp(0, consts::MAX_GARBAGE_PER_PLACE as i64) as i32;
+        });
+        engine.register_fn("gravity", |game: &mut Game, ms: i64| {
+            game.0.borrow_mut().gravity = Some(Duration::from_millis(ms.max(1) as u64));
+        });
+        engine.register_fn("end", |game: &mut Game, message: &str| {
+            game.0.borrow_mut().end = Some(message.to_string());
+        });
+        engine.register_get_set(
+            "hud",
+            |game: &mut Game| game.0.borrow().hud.clone(),
+            |game: &mut Game, hud: String| game.0.borrow_mut().hud = hud,
+        );
+        engine.register_get_set(
+            "vars",
+            |game: &mut Game| game.0.borrow().vars.clone(),
+            |game: &mut Game, vars: rhai::Map| game.0.borrow_mut().vars = vars,
+        );
+
+        let ast = engine.compile(source).map_err(|error| error.to_string())?;
+
+        Ok(Self {
+            engine,
+            ast,
+            game: Game(Rc::new(RefCell::new(Requests::default()))),
+        })
+    }
+
+    pub fn requests(&self) -> std::cell::RefMut<'_, Requests> {
+        self.game.0.borrow_mut()
+    }
+
+    // runs a hook when the script defines it with that many arguments after the game
+    pub fn call(&mut self, hook: &str, args: Vec<rhai::Dynamic>) -> Result<(), String> {
+        let defined = self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == hook && function.params.len() == args.len() + 1);
+        if !defined {
+            return Ok(());
+        }
+
+        let mut all_args = vec![rhai::Dynamic::from(self.game.clone())];
+        all_args.extend(args);
+
+        self.engine
+            .call_fn_with_options::<rhai::Dynamic>(
+                rhai::CallFnOptions::new().eval_ast(false),
+                &mut rhai::Scope::new(),
+                &self.ast,
+                hook,
+                all_args,
+            )
+            .map(|_| ())
+            .map_err(|error| format!("{}: {}", hook, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(width: usize, height: usize) -> Vec<Vec<Option<consts::BlockColor>>> {
+        vec![vec![None; width]; height]
+    }
+
+    #[test]
+    fn hooks_read_and_edit_the_game() {
+        let mut script = Script::compile(
+            r#"
+            fn on_lock(game, piece, lines, t_spin) {
+                game.set(0, 0, piece);
+                game.set(game.width - 1, game.height - 1, "G");
+                game.hud = `${piece} ${lines} ${t_spin} ${game.get(0, 0)}`;
+                game.garbage(lines);
+                game.gravity(100);
+            }
+            "#,
+        )
+        .unwrap();
+        script.requests().blocks = board(4, 3);
+        script
+            .call("on_lock", vec!["T".into(), 2_i64.into(), true.into()])
+            .unwrap();
+
+        let requests = script.requests();
+        assert!(requests.blocks_edited);
+        assert_eq!(
+            game::board_text::format(&requests.blocks),
+            "...G\n....\nT...\n"
+        );
+        assert_eq!(requests.hud, "T 2 true T");
+        assert_eq!(requests.garbage, 2);
+        assert_eq!(requests.gravity, Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn vars_last_between_hooks_and_missing_hooks_are_skipped() {
+        let mut script = Script::compile(
+            r#"
+            fn on_start(game) {
+                game.vars.count = 0;
+            }
+            fn on_tick(game, ms) {
+                game.vars.count += ms;
+                if game.vars.count >= 30 {
+                    game.end("done");
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        script.call("on_start", vec![]).unwrap();
+        script
+            .call("on_clear", vec![rhai::Array::new().into()])
+            .unwrap();
+        for _ in 0..3 {
+            script.call("on_tick", vec![10_i64.into()]).unwrap();
+        }
+
+        assert_eq!(script.requests().end.as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn runaway_and_broken_scripts_fail() {
+        let mut script = Script::compile("fn on_start(game) { loop {} }").unwrap();
+        assert!(script.call("on_start", vec![]).is_err());
+
+        let mut script = Script::compile("fn on_start(game) { game.set(99, 0, \"G\"); }").unwrap();
+        script.requests().blocks = board(4, 3);
+        assert!(script.call("on_start", vec![]).is_err());
+
+        assert!(Script::compile("fn on_start(game) {").is_err());
+    }
+
+    #[test]
+    fn example_scripts_run_every_hook() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/scripts");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let result = Script::load(path.to_str().unwrap()).and_then(|mut script| {
+                script.requests().blocks = board(10, 25);
+                script.call("on_start", vec![])?;
+                script.call("on_tick", vec![16_i64.into()])?;
+                script.call("on_lock", vec!["T".into(), 1_i64.into(), false.into()])?;
+                script.call("on_clear", vec![vec![rhai::Dynamic::from(0_i64)].into()])?;
+                script.call("on_top_out", vec!["lock out".into()])
+            });
+            if let Err(error) = result {
+                panic!("{}: {}", path.display(), error);
+            }
+        }
+    }
+}
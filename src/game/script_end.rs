@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
+    pub score: &'a i32,
+    pub message: &'a str,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    config: game::playing::Config,
+    path: String,
+    score: i32,
+    message: String,
+}
+
+impl GameSystem {
+    pub fn new(
+        blocks: Vec<Vec<Option<consts::BlockColor>>>,
+        config: game::playing::Config,
+        path: String,
+        score: i32,
+        message: String,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            blocks,
+            config,
+            path,
+            score,
+            message,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        // the script is read again, so edits show up on the next play
+                        KeyCode::Enter => {
+                            match game::scripted::GameSystem::new(&self.path, self.config.clone()) {
+                                Ok(system) => {
+                                    let state = game::GameSystem::Scripted(system);
+                                    *flow = game::GameSystemFlow::To(state);
+                                }
+                                Err(error) => self.message = format!("SCRIPT ERROR\n{}", error),
+                            }
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            blocks: &self.blocks,
+            dimensions: &self.config.dimensions,
+            score: &self.score,
+            message: &self.message,
+        }
+    }
+}
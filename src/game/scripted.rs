@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use crate::game::script::Script;
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub hud: &'a str,
+}
+
+// plays a mode whose rules come from a script, called on the board events as they happen
+pub struct GameSystem {
+    board: game::playing::GameSystem,
+    script: Script,
+    path: String,
+    hud: String,
+    // the board events, kept for observers once the script has seen them
+    events: Vec<game::playing::Event>,
+    started: bool,
+
+    last_update: Option<Duration>,
+}
+
+impl GameSystem {
+    pub fn new(path: &str, config: game::playing::Config) -> Result<Self, String> {
+        let board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            rand::random(),
+            config,
+        );
+
+        Ok(Self {
+            board,
+            script: Script::load(path)?,
+            path: path.to_string(),
+            hud: String::new(),
+            events: vec![],
+            started: false,
+
+            last_update: None,
+        })
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_run_hooks(board_flow, flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        if !self.started {
+            self.started = true;
+            self.call("on_start", vec![], flow);
+        }
+
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if let Some(delta_time) = delta_time {
+            if !*self.board.context().paused {
+                let ms = delta_time.as_millis() as i64;
+                self.call("on_tick", vec![ms.into()], flow);
+            }
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_run_hooks(board_flow, flow);
+    }
+
+    // passes the board events to the script, a transition out of the board meaning it topped out
+    fn check_and_run_hooks(
+        &mut self,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        let piece_set = self.board.config().piece_set.clone();
+
+        for event in self.board.take_events() {
+            match &event {
+                game::playing::Event::Locked { piece, placement } => {
+                    let letter = game::rotation::Tetromino::of(&piece_set.pieces[*piece])
+                        .map(|tetromino| tetromino.letter().to_string())
+                        .unwrap_or_default();
+                    let args = vec![
+                        letter.into(),
+                        (placement.lines as i64).into(),
+                        placement.t_spin.into(),
+                    ];
+                    self.call("on_lock", args, flow);
+                }
+                game::playing::Event::LinesCleared { rows } => {
+                    let rows = rows
+                        .iter()
+                        .map(|row| (*row as i64).into())
+                        .collect::<rhai::Array>();
+                    self.call("on_clear", vec![rows.into()], flow);
+                }
+                game::playing::Event::ToppedOut(top_out) => {
+                    let reason = top_out_reason(top_out).to_string();
+                    self.call("on_top_out", vec![reason.into()], flow);
+                }
+                _ => {}
+            }
+            self.events.push(event);
+        }
+
+        if let game::GameSystemFlow::To(_) = board_flow {
+            if let game::GameSystemFlow::Default = flow {
+                self.finish("TOP OUT".to_string(), flow);
+            }
+        }
+    }
+
+    // runs a hook on the board as it is now and applies what the script asked for, ending the game
+    // when the script asks to or fails
+    fn call(&mut self, hook: &str, args: Vec<rhai::Dynamic>, flow: &mut game::GameSystemFlow) {
+        if let game::GameSystemFlow::To(_) = flow {
+            return;
+        }
+
+        {
+            let context = self.board.context();
+            let mut requests = self.script.requests();
+            requests.blocks = context.blocks.clone();
+            requests.fixed = fixed_cells(&context);
+            requests.score = *context.score;
+            requests.lines = *context.lines;
+            requests.hud = std::mem::take(&mut self.hud);
+        }
+
+        let result = self.script.call(hook, args);
+
+        let (blocks, garbage, gravity, end) = {
+            let mut requests = self.script.requests();
+            self.hud = std::mem::take(&mut requests.hud);
+            let blocks = std::mem::take(&mut requests.blocks);
+            (
+                std::mem::take(&mut requests.blocks_edited).then_some(blocks),
+                std::mem::take(&mut requests.garbage),
+                requests.gravity.take(),
+                requests.end.take(),
+            )
+        };
+
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            self.finish(format!("SCRIPT ERROR\n{}", error), flow);
+            return;
+        }
+
+        if let Some(blocks) = blocks {
+            self.board.set_blocks(blocks);
+        }
+        if 0 < garbage {
+            let mut board_flow = game::GameSystemFlow::Default;
+            let placement = game::garbage::HolePlacement::Messiness(consts::SCRIPT_MESSINESS);
+            self.board
+                .insert_garbage(garbage, placement, &mut board_flow);
+            if let game::GameSystemFlow::To(_) = board_flow {
+                self.finish("TOP OUT".to_string(), flow);
+                return;
+            }
+        }
+        if let Some(gravity) = gravity {
+            let config = self.board.config().clone();
+            self.board.set_timing(
+                game::playing::Gravity::Fixed(gravity),
+                config.entry_delay,
+                config.line_clear_delay,
+                config.lock_delay,
+                config.auto_shift,
+            );
+        }
+        if let Some(message) = end {
+            self.finish(message, flow);
+        }
+    }
+
+    fn finish(&mut self, message: String, flow: &mut game::GameSystemFlow) {
+        let state = game::GameSystem::ScriptEnd(game::script_end::GameSystem::new(
+            self.board.context().blocks.clone(),
+            self.board.config().clone(),
+            self.path.clone(),
+            *self.board.context().score,
+            message,
+        ));
+        *flow = game::GameSystemFlow::To(state);
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            hud: &self.hud,
+        }
+    }
+}
+
+// setting these would leave the falling mino inside the stack, or a clear taking out a line that
+// is no longer full
+fn fixed_cells(context: &game::playing::GameContext) -> Vec<(usize, usize)> {
+    let mino_cells = context
+        .active_mino
+        .iter()
+        .flat_map(|mino| {
+            mino.blocks
+                .iter()
+                .map(move |(x, y)| (mino.x + x, mino.y + y))
+        })
+        .filter_map(|(x, y)| Some((usize::try_from(x).ok()?, usize::try_from(y).ok()?)));
+    let width = context.dimensions.width as usize;
+    let line_cells = context
+        .clearing_lines
+        .iter()
+        .flat_map(|row| (0..width).map(move |x| (x, *row)));
+
+    mino_cells.chain(line_cells).collect()
+}
+
+fn top_out_reason(top_out: &game::playing::TopOut) -> &'static str {
+    match top_out {
+        game::playing::TopOut::BlockOut => "block out",
+        game::playing::TopOut::LockOut => "lock out",
+        game::playing::TopOut::PartialLockOut => "partial lock out",
+        game::playing::TopOut::GarbageOut => "garbage out",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_cannot_set_cells_under_the_falling_mino() {
+        let path = std::env::temp_dir().join("tetris-wgpu-fixed-cells.rhai");
+        std::fs::write(
+            &path,
+            r#"
+            fn on_start(game) {
+                game.vars.elapsed = 0;
+            }
+            fn on_tick(game, ms) {
+                game.vars.elapsed += ms;
+                if game.vars.elapsed < 1000 {
+                    return;
+                }
+                game.set(0, 0, "G");
+                for y in 0..game.height {
+                    for x in 0..game.width {
+                        game.set(x, y, "G");
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let mut system = GameSystem::new(path.to_str().unwrap(), Default::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let clock = game::clock::ManualClock::new();
+        let mut flow = game::GameSystemFlow::Default;
+        for _ in 0..20 {
+            system.update(&clock, &mut flow);
+            clock.advance(Duration::from_millis(100));
+        }
+        assert!(system.context().board.active_mino.is_some());
+
+        match flow {
+            game::GameSystemFlow::To(game::GameSystem::ScriptEnd(end)) => {
+                assert!(end.context().message.contains("falling mino"));
+            }
+            _ => panic!("setting a cell under the mino didn't fail the script"),
+        }
+        // the edits before the failing one are dropped along with it
+        assert_eq!(system.context().board.blocks[0][0], None);
+    }
+}
@@ -1,46 +1,546 @@
 use std::collections::HashSet;
+use std::path::PathBuf;
 
-use crate::game;
+use crate::game::menu::{Choice, Item, Menu};
+use crate::{consts, game};
 
-pub struct GameContext;
+pub struct GameContext<'a> {
+    pub menu: &'a Menu,
+    pub footer: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Main,
+    Play,
+    Replays,
+    HighScores,
+    Settings,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Play,
+    Continue,
+    Replays,
+    HighScores,
+    Settings,
+    Quit,
+}
+
+const ENTRIES: &[(Entry, &str)] = &[
+    (Entry::Play, "PLAY"),
+    (Entry::Continue, "CONTINUE"),
+    (Entry::Replays, "REPLAYS"),
+    (Entry::HighScores, "HIGH SCORES"),
+    (Entry::Settings, "SETTINGS"),
+    (Entry::Quit, "QUIT"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Marathon,
+    Classic,
+    Master,
+    Survival,
+    Cheese,
+    Versus,
+    Royale,
+    Practice,
+    Puzzles,
+    Editor,
+}
+
+// every mode with the key that starts it straight from the main page
+const MODES: &[(Mode, &str, Option<winit::keyboard::KeyCode>)] = &[
+    (Mode::Marathon, "MARATHON", None),
+    (
+        Mode::Classic,
+        "CLASSIC",
+        Some(winit::keyboard::KeyCode::KeyN),
+    ),
+    (Mode::Master, "MASTER", Some(winit::keyboard::KeyCode::KeyM)),
+    (
+        Mode::Survival,
+        "SURVIVAL",
+        Some(winit::keyboard::KeyCode::KeyS),
+    ),
+    (
+        Mode::Cheese,
+        "CHEESE RACE",
+        Some(winit::keyboard::KeyCode::KeyC),
+    ),
+    (Mode::Versus, "VERSUS", Some(winit::keyboard::KeyCode::KeyV)),
+    (
+        Mode::Royale,
+        "BATTLE ROYALE",
+        Some(winit::keyboard::KeyCode::KeyB),
+    ),
+    (
+        Mode::Practice,
+        "PRACTICE",
+        Some(winit::keyboard::KeyCode::KeyP),
+    ),
+    (
+        Mode::Puzzles,
+        "PUZZLES",
+        Some(winit::keyboard::KeyCode::KeyZ),
+    ),
+    (Mode::Editor, "EDITOR", Some(winit::keyboard::KeyCode::KeyE)),
+];
+
+// the piece set and rotation system picked in the settings, kept between runs by name
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Settings {
+    pieces: String,
+    rotation: String,
+}
 
+// the title screen, a menu of pages for picking a mode, going back to a game left for it, looking
+// through the records and changing the settings
 pub struct GameSystem {
     pressed: HashSet<winit::keyboard::KeyCode>,
+
+    page: Page,
+    menu: Menu,
+    message: Option<String>,
+
+    piece_set: usize,
+    rotation_system: usize,
+    records: game::records::Records,
+    scripts: Vec<PathBuf>,
+    // the game escape left, until another one starts
+    suspended: Option<game::playing::GameSystem>,
 }
 
 impl GameSystem {
     pub fn new() -> Self {
-        Self {
+        let settings = std::fs::read_to_string(consts::SETTINGS_PATH)
+            .ok()
+            .and_then(|source| toml::from_str::<Settings>(&source).ok())
+            .unwrap_or_default();
+
+        let mut system = Self {
             pressed: HashSet::new(),
+
+            page: Page::Main,
+            menu: Menu::new("TETRIS", vec![]),
+            message: None,
+
+            piece_set: game::pieces::PieceSet::builtin()
+                .iter()
+                .position(|piece_set| piece_set.name.eq_ignore_ascii_case(&settings.pieces))
+                .unwrap_or(0),
+            rotation_system: game::rotation::SYSTEMS
+                .iter()
+                .position(|system| system.name().eq_ignore_ascii_case(&settings.rotation))
+                .unwrap_or(0),
+            records: game::records::Records::load(),
+            scripts: scripts(),
+            suspended: None,
+        };
+        system.open(Page::Main);
+        system
+    }
+
+    // the menu with continue selected, for the game that was left
+    pub fn with_suspended(board: game::playing::GameSystem) -> Self {
+        let mut system = Self::new();
+        system.suspended = Some(board);
+        system.open(Page::Main);
+        system.menu.select(entry_index(Entry::Continue));
+        system
+    }
+
+    fn config(&self) -> game::playing::Config {
+        game::playing::Config {
+            piece_set: game::pieces::PieceSet::builtin()[self.piece_set].clone(),
+            rotation_system: game::rotation::SYSTEMS[self.rotation_system],
+            replay: true,
+            ..Default::default()
         }
     }
 
+    fn save_settings(&self) {
+        let settings = Settings {
+            pieces: game::pieces::PieceSet::builtin()[self.piece_set]
+                .name
+                .clone(),
+            rotation: game::rotation::SYSTEMS[self.rotation_system]
+                .name()
+                .to_string(),
+        };
+        let result = toml::to_string(&settings)
+            .map_err(|error| error.to_string())
+            .and_then(|source| {
+                std::fs::write(consts::SETTINGS_PATH, source).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            eprintln!("failed to save settings: {}", error);
+        }
+    }
+
+    fn open(&mut self, page: Page) {
+        let (title, items) = match page {
+            Page::Main => (
+                "TETRIS",
+                ENTRIES
+                    .iter()
+                    .map(|(entry, label)| {
+                        Item::new(*label)
+                            .enabled(*entry != Entry::Continue || self.suspended.is_some())
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Page::Play => (
+                "PLAY",
+                MODES
+                    .iter()
+                    .map(|(_, label, _)| Item::new(*label))
+                    .chain(
+                        self.scripts
+                            .iter()
+                            .map(|path| Item::new(format!("SCRIPT: {}", script_name(path)))),
+                    )
+                    .collect(),
+            ),
+            Page::Replays => (
+                "REPLAYS",
+                if self.records.replays.is_empty() {
+                    vec![Item::new("NO REPLAYS YET").enabled(false)]
+                } else {
+                    self.records
+                        .replays
+                        .iter()
+                        .enumerate()
+                        .map(|(index, replay)| {
+                            Item::new(format!(
+                                "{}. SCORE {}  LINES {}",
+                                index + 1,
+                                replay.score,
+                                replay.lines
+                            ))
+                        })
+                        .collect()
+                },
+            ),
+            Page::HighScores => (
+                "HIGH SCORES",
+                if self.records.high_scores.is_empty() {
+                    vec![Item::new("NO SCORES YET").enabled(false)]
+                } else {
+                    self.records
+                        .high_scores
+                        .iter()
+                        .enumerate()
+                        .map(|(index, high_score)| {
+                            Item::new(format!(
+                                "{}. {}  {} LINES  {} {}",
+                                index + 1,
+                                high_score.score,
+                                high_score.lines,
+                                high_score.pieces.to_uppercase(),
+                                high_score.rotation
+                            ))
+                        })
+                        .collect()
+                },
+            ),
+            Page::Settings => (
+                "SETTINGS",
+                vec![
+                    Item::new("PIECES").with_value(
+                        game::pieces::PieceSet::builtin()[self.piece_set]
+                            .name
+                            .to_uppercase(),
+                    ),
+                    Item::new("ROTATION")
+                        .with_value(game::rotation::SYSTEMS[self.rotation_system].name()),
+                ],
+            ),
+        };
+
+        let mut items = items;
+        if page != Page::Main {
+            items.push(Item::new("BACK"));
+        }
+
+        // the selection stays put when a page is rebuilt in place
+        if page != self.page {
+            self.menu = Menu::new(title, items);
+        } else {
+            self.menu.title = title.to_string();
+            self.menu.set_items(items);
+        }
+        self.page = page;
+    }
+
     pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
         use winit::event::ElementState;
-        use winit::keyboard::KeyCode;
 
-        if let winit::keyboard::PhysicalKey::Code(virtual_keycode) = input.physical_key {
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
             match input.state {
-                ElementState::Pressed if !self.pressed.contains(&virtual_keycode) => {
-                    if virtual_keycode == KeyCode::Enter {
-                        let state = game::GameSystem::Playing(game::playing::GameSystem::new());
-                        *flow = game::GameSystemFlow::To(state);
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    let shortcut = MODES
+                        .iter()
+                        .find(|(_, _, key)| *key == Some(code))
+                        .filter(|_| self.page == Page::Main);
+                    if let Some((mode, _, _)) = shortcut {
+                        *flow = game::GameSystemFlow::To(self.start(*mode));
+                    } else if let Some(choice) = self.menu.key(code) {
+                        self.choose(choice, flow);
                     }
-                    self.pressed.insert(virtual_keycode);
+                    self.pressed.insert(code);
                 }
                 ElementState::Released => {
-                    self.pressed.remove(&virtual_keycode);
+                    self.pressed.remove(&code);
                 }
                 _ => {}
             }
         }
     }
 
-    pub fn update(&mut self, _flow: &mut game::GameSystemFlow) {
+    pub fn mouse_input(&mut self, input: &game::MouseInput, flow: &mut game::GameSystemFlow) {
+        if let Some(choice) = self.menu.mouse_input(input) {
+            self.choose(choice, flow);
+        }
+    }
+
+    fn choose(&mut self, choice: Choice, flow: &mut game::GameSystemFlow) {
+        // the last item of every page but the main one goes back to it
+        let choice = match choice {
+            Choice::Chosen(index)
+                if self.page != Page::Main && index + 1 == self.menu.items.len() =>
+            {
+                Choice::Back
+            }
+            choice => choice,
+        };
+        self.message = None;
+
+        match (self.page, choice) {
+            (Page::Main, Choice::Back) => {}
+            (page, Choice::Back) => {
+                self.open(Page::Main);
+                let entry = match page {
+                    Page::Play => Entry::Play,
+                    Page::Replays => Entry::Replays,
+                    Page::HighScores => Entry::HighScores,
+                    _ => Entry::Settings,
+                };
+                self.menu.select(entry_index(entry));
+            }
+            (Page::Main, Choice::Chosen(index)) => match ENTRIES[index].0 {
+                Entry::Play => self.open(Page::Play),
+                Entry::Continue => {
+                    if let Some(mut board) = self.suspended.take() {
+                        board.set_paused(false);
+                        *flow = game::GameSystemFlow::To(game::GameSystem::Playing(board));
+                    }
+                }
+                Entry::Replays => self.open(Page::Replays),
+                Entry::HighScores => self.open(Page::HighScores),
+                Entry::Settings => self.open(Page::Settings),
+                Entry::Quit => *flow = game::GameSystemFlow::Quit,
+            },
+            (Page::Play, Choice::Chosen(index)) => {
+                if let Some((mode, _, _)) = MODES.get(index) {
+                    *flow = game::GameSystemFlow::To(self.start(*mode));
+                } else {
+                    let path = &self.scripts[index - MODES.len()];
+                    match game::scripted::GameSystem::new(&path.to_string_lossy(), self.config()) {
+                        Ok(system) => {
+                            *flow = game::GameSystemFlow::To(game::GameSystem::Scripted(system));
+                        }
+                        Err(error) => {
+                            eprintln!("failed to load script {}: {}", path.display(), error);
+                            self.message = Some("THE SCRIPT FAILED TO LOAD".to_string());
+                        }
+                    }
+                }
+            }
+            // there is no replay viewer, so a replay goes to the clipboard for a fumen viewer
+            (Page::Replays, Choice::Chosen(index)) => {
+                let fumen = &self.records.replays[index].fumen;
+                self.message = Some(match game::fumen::copy_to_clipboard(fumen) {
+                    Ok(()) => "COPIED TO THE CLIPBOARD AS A FUMEN".to_string(),
+                    Err(error) => {
+                        eprintln!("{}", error);
+                        "FAILED TO COPY TO THE CLIPBOARD".to_string()
+                    }
+                });
+            }
+            (Page::Settings, Choice::Chosen(index) | Choice::Next(index)) => {
+                self.change_setting(index, true)
+            }
+            (Page::Settings, Choice::Previous(index)) => self.change_setting(index, false),
+            _ => {}
+        }
+    }
+
+    fn change_setting(&mut self, index: usize, forward: bool) {
+        let (value, count) = match index {
+            0 => (&mut self.piece_set, game::pieces::PieceSet::builtin().len()),
+            1 => (&mut self.rotation_system, game::rotation::SYSTEMS.len()),
+            _ => return,
+        };
+        *value = if forward {
+            (*value + 1) % count
+        } else {
+            (*value + count - 1) % count
+        };
+
+        self.save_settings();
+        self.open(Page::Settings);
+    }
+
+    fn start(&self, mode: Mode) -> game::GameSystem {
+        match mode {
+            Mode::Marathon => game::GameSystem::Playing(game::playing::GameSystem::with_config(
+                game::playing::DEFAULT_BINDINGS,
+                rand::random(),
+                self.config(),
+            )),
+            Mode::Classic => game::GameSystem::Classic(game::classic::GameSystem::new()),
+            Mode::Master => game::GameSystem::Master(game::master::GameSystem::new()),
+            Mode::Survival => {
+                game::GameSystem::Survival(game::survival::GameSystem::new(self.config()))
+            }
+            Mode::Cheese => game::GameSystem::Cheese(game::cheese::GameSystem::new(
+                consts::CHEESE_DEFAULT_LINES,
+                game::garbage::HolePlacement::Random,
+                self.config(),
+            )),
+            Mode::Versus => game::GameSystem::Versus(game::versus::GameSystem::new()),
+            Mode::Royale => {
+                game::GameSystem::Royale(game::royale::GameSystem::new(consts::ROYALE_DEFAULT_BOTS))
+            }
+            Mode::Practice => {
+                game::GameSystem::Practice(game::practice::GameSystem::new(self.config()))
+            }
+            Mode::Puzzles => game::GameSystem::PuzzleSelect(game::puzzle_select::GameSystem::new()),
+            Mode::Editor => game::GameSystem::Editor(game::editor::GameSystem::new(self.config())),
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
         // nothing
     }
 
-    pub fn context(&self) -> GameContext {
-        GameContext
+    pub fn context(&'_ self) -> GameContext<'_> {
+        let help = match self.page {
+            // the letters that start a mode straight away, named after their key codes
+            Page::Main => {
+                let shortcuts = MODES
+                    .iter()
+                    .filter_map(|(_, name, key)| {
+                        let key = format!("{:?}", key.as_ref()?);
+                        Some(format!("{}: {}", key.trim_start_matches("Key"), name))
+                    })
+                    .collect::<Vec<_>>();
+                let rows = shortcuts
+                    .chunks(consts::MENU_SHORTCUTS_PER_ROW)
+                    .map(|row| row.join("    "))
+                    .collect::<Vec<_>>();
+                format!("UP/DOWN: SELECT    RETURN: CHOOSE\n{}", rows.join("\n"))
+            }
+            Page::Replays => "RETURN: COPY AS FUMEN    ESCAPE: BACK".to_string(),
+            Page::Settings => "LEFT/RIGHT: CHANGE    ESCAPE: BACK".to_string(),
+            _ => "UP/DOWN: SELECT    RETURN: CHOOSE    ESCAPE: BACK".to_string(),
+        };
+
+        GameContext {
+            menu: &self.menu,
+            footer: match &self.message {
+                Some(message) => format!("{}\n{}", message, help),
+                None => help,
+            },
+        }
+    }
+}
+
+fn entry_index(entry: Entry) -> usize {
+    ENTRIES
+        .iter()
+        .position(|(other, _)| *other == entry)
+        .unwrap()
+}
+
+// the scripts in the scripts folder, by file name
+fn scripts() -> Vec<PathBuf> {
+    let mut scripts = std::fs::read_dir(consts::SCRIPTS_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "rhai")
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    scripts.sort();
+    scripts
+}
+
+fn script_name(path: &std::path::Path) -> String {
+    path.file_stem()
+        .map_or(String::new(), |stem| stem.to_string_lossy().to_string())
+        .replace('_', " ")
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continue_resumes_the_game_escape_left() {
+        let mut board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            0,
+            Default::default(),
+        );
+        board.set_paused(true);
+        let mut system = GameSystem::with_suspended(board);
+        assert_eq!(system.menu.selected, entry_index(Entry::Continue));
+
+        let mut flow = game::GameSystemFlow::Default;
+        system.choose(Choice::Chosen(system.menu.selected), &mut flow);
+        match flow {
+            game::GameSystemFlow::To(game::GameSystem::Playing(board)) => {
+                assert!(!*board.context().paused)
+            }
+            _ => panic!("continue didn't go back to the game"),
+        }
+    }
+
+    #[test]
+    fn pages_go_back_to_the_entry_they_were_opened_from() {
+        let mut system = GameSystem::new();
+        assert!(!system.menu.items[entry_index(Entry::Continue)].enabled);
+
+        let mut flow = game::GameSystemFlow::Default;
+        system.choose(Choice::Chosen(entry_index(Entry::HighScores)), &mut flow);
+        assert!(system.page == Page::HighScores);
+        system.choose(Choice::Chosen(system.menu.items.len() - 1), &mut flow);
+        assert!(system.page == Page::Main);
+        assert_eq!(system.menu.selected, entry_index(Entry::HighScores));
+        assert!(matches!(flow, game::GameSystemFlow::Default));
+
+        system.choose(Choice::Chosen(entry_index(Entry::Quit)), &mut flow);
+        assert!(matches!(flow, game::GameSystemFlow::Quit));
+    }
+
+    #[test]
+    fn the_main_page_lists_its_keys() {
+        let system = GameSystem::new();
+        let footer = system.context().footer;
+
+        assert!(footer.starts_with("UP/DOWN: SELECT    RETURN: CHOOSE\n"));
+        assert!(footer.contains("N: CLASSIC"));
+        assert!(footer.contains("E: EDITOR"));
+        assert!(!footer.contains("ROTATE"));
     }
 }
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub board: game::playing::GameContext<'a>,
+    pub elapsed: &'a Duration,
+}
+
+// holds out against garbage rising from the bottom for as long as possible
+pub struct GameSystem {
+    board: game::playing::GameSystem,
+
+    last_update: Option<Duration>,
+    elapsed: Duration,
+}
+
+impl GameSystem {
+    pub fn new(config: game::playing::Config) -> Self {
+        let mut board = game::playing::GameSystem::with_config(
+            game::playing::DEFAULT_BINDINGS,
+            rand::random(),
+            config,
+        );
+        board.set_rising_garbage(consts::SURVIVAL_START_INTERVAL);
+
+        Self {
+            board,
+
+            last_update: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.input(input, &mut board_flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        let now = clock.now();
+        let delta_time = self
+            .last_update
+            .map(|last_update| now.saturating_sub(last_update));
+        self.last_update = Some(now);
+
+        if let Some(delta_time) = delta_time {
+            if !*self.board.context().paused {
+                self.elapsed += delta_time;
+            }
+        }
+
+        let mut board_flow = game::GameSystemFlow::Default;
+        self.board.update(clock, &mut board_flow);
+        self.check_and_finish(board_flow, flow);
+    }
+
+    // a transition out of the board means it topped out
+    fn check_and_finish(
+        &mut self,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        if let game::GameSystemFlow::To(_) = board_flow {
+            let state = game::GameSystem::SurvivalEnd(game::survival_end::GameSystem::new(
+                self.board.context().blocks.clone(),
+                self.board.config().clone(),
+                self.elapsed,
+                *self.board.context().lines,
+                self.board.topped_out(),
+            ));
+            *flow = game::GameSystemFlow::To(state);
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<game::playing::Event> {
+        self.board.take_events()
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            board: self.board.context(),
+            elapsed: &self.elapsed,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::{consts, game};
+
+pub struct GameContext<'a> {
+    pub blocks: &'a Vec<Vec<Option<consts::BlockColor>>>,
+    pub dimensions: &'a consts::Dimensions,
+    pub elapsed: &'a Duration,
+    pub lines: &'a i32,
+    pub top_out: &'a Option<game::playing::TopOut>,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    blocks: Vec<Vec<Option<consts::BlockColor>>>,
+    config: game::playing::Config,
+    elapsed: Duration,
+    lines: i32,
+    top_out: Option<game::playing::TopOut>,
+}
+
+impl GameSystem {
+    pub fn new(
+        blocks: Vec<Vec<Option<consts::BlockColor>>>,
+        config: game::playing::Config,
+        elapsed: Duration,
+        lines: i32,
+        top_out: Option<game::playing::TopOut>,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            blocks,
+            config,
+            elapsed,
+            lines,
+            top_out,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Enter => {
+                            let state = game::GameSystem::Survival(
+                                game::survival::GameSystem::new(self.config.clone()),
+                            );
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            blocks: &self.blocks,
+            dimensions: &self.config.dimensions,
+            elapsed: &self.elapsed,
+            lines: &self.lines,
+            top_out: &self.top_out,
+        }
+    }
+}
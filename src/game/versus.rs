@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+
+use crate::game;
+use crate::game::playing::{Action, Bindings};
+
+#[rustfmt::skip]
+pub const PLAYER_1_BINDINGS: Bindings = &[
+    (winit::keyboard::KeyCode::KeyA,      Action::MoveLeft),
+    (winit::keyboard::KeyCode::KeyD,      Action::MoveRight),
+    (winit::keyboard::KeyCode::KeyS,      Action::SoftDrop),
+    (winit::keyboard::KeyCode::KeyW,      Action::HardDrop),
+    (winit::keyboard::KeyCode::KeyE,      Action::RotateClockwise),
+    (winit::keyboard::KeyCode::KeyQ,      Action::RotateCounterClockwise),
+    (winit::keyboard::KeyCode::KeyR,      Action::RotateFlip),
+    (winit::keyboard::KeyCode::ShiftLeft, Action::Hold),
+];
+
+#[rustfmt::skip]
+pub const PLAYER_2_BINDINGS: Bindings = &[
+    (winit::keyboard::KeyCode::ArrowLeft,  Action::MoveLeft),
+    (winit::keyboard::KeyCode::ArrowRight, Action::MoveRight),
+    (winit::keyboard::KeyCode::ArrowDown,  Action::SoftDrop),
+    (winit::keyboard::KeyCode::ArrowUp,    Action::HardDrop),
+    (winit::keyboard::KeyCode::Period,     Action::RotateClockwise),
+    (winit::keyboard::KeyCode::Comma,      Action::RotateCounterClockwise),
+    (winit::keyboard::KeyCode::Slash,      Action::RotateFlip),
+    (winit::keyboard::KeyCode::ShiftRight, Action::Hold),
+];
+
+pub struct GameContext<'a> {
+    pub boards: [game::playing::GameContext<'a>; 2],
+    pub paused: &'a bool,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    boards: [game::playing::GameSystem; 2],
+
+    paused: bool,
+}
+
+impl GameSystem {
+    pub fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            boards: [
+                game::playing::GameSystem::with_bindings(PLAYER_1_BINDINGS, rand::random()),
+                game::playing::GameSystem::with_bindings(PLAYER_2_BINDINGS, rand::random()),
+            ],
+
+            paused: false,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    if code == KeyCode::KeyP {
+                        self.paused = !self.paused;
+                        self.boards
+                            .iter_mut()
+                            .for_each(|board| board.set_paused(self.paused));
+                    } else {
+                        for index in 0..self.boards.len() {
+                            if let Some(action) = self.boards[index].binding(code) {
+                                let mut board_flow = game::GameSystemFlow::Default;
+                                self.boards[index].action(action, &mut board_flow);
+                                self.check_and_finish(index, board_flow, flow);
+                            }
+                        }
+                        self.check_and_send_garbage();
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, clock: &dyn game::clock::Clock, flow: &mut game::GameSystemFlow) {
+        for index in 0..self.boards.len() {
+            let mut board_flow = game::GameSystemFlow::Default;
+            self.boards[index].update(clock, &mut board_flow);
+            self.check_and_finish(index, board_flow, flow);
+        }
+        self.check_and_send_garbage();
+    }
+
+    fn check_and_send_garbage(&mut self) {
+        let [first, second] = &mut self.boards;
+
+        second.receive_garbage(first.take_outgoing_garbage());
+        first.receive_garbage(second.take_outgoing_garbage());
+    }
+
+    // a board that wants to leave the playing state has topped out, so the other player wins
+    fn check_and_finish(
+        &self,
+        loser: usize,
+        board_flow: game::GameSystemFlow,
+        flow: &mut game::GameSystemFlow,
+    ) {
+        if let game::GameSystemFlow::To(_) = board_flow {
+            if let game::GameSystemFlow::To(_) = flow {
+                return;
+            }
+
+            let boards = self.boards.each_ref().map(|board| {
+                let cx = board.context();
+                (cx.blocks.clone(), *cx.score)
+            });
+            let dimensions = self.boards[0].dimensions();
+            let outcome = game::versus_end::Outcome::Winner(1 - loser);
+            let state = game::GameSystem::VersusEnd(game::versus_end::GameSystem::new(
+                boards, dimensions, outcome, false,
+            ));
+            *flow = game::GameSystemFlow::To(state);
+        }
+    }
+
+    // the events of both boards, player 1 first
+    pub fn take_events(&mut self) -> [Vec<game::playing::Event>; 2] {
+        self.boards.each_mut().map(|board| board.take_events())
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            boards: self.boards.each_ref().map(|board| board.context()),
+            paused: &self.paused,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::{consts, game};
+
+#[derive(Clone, Copy)]
+pub enum Outcome {
+    Winner(usize),
+    Disconnected,
+    Desynced,
+}
+
+pub struct GameContext<'a> {
+    pub boards: &'a [(Vec<Vec<Option<consts::BlockColor>>>, i32); 2],
+    pub dimensions: &'a consts::Dimensions,
+    pub outcome: &'a Outcome,
+    pub online: &'a bool,
+}
+
+pub struct GameSystem {
+    pressed: HashSet<winit::keyboard::KeyCode>,
+
+    boards: [(Vec<Vec<Option<consts::BlockColor>>>, i32); 2],
+    dimensions: consts::Dimensions,
+    outcome: Outcome,
+    online: bool,
+}
+
+impl GameSystem {
+    pub fn new(
+        boards: [(Vec<Vec<Option<consts::BlockColor>>>, i32); 2],
+        dimensions: consts::Dimensions,
+        outcome: Outcome,
+        online: bool,
+    ) -> Self {
+        Self {
+            pressed: HashSet::new(),
+
+            boards,
+            dimensions,
+            outcome,
+            online,
+        }
+    }
+
+    pub fn input(&mut self, input: &winit::event::KeyEvent, flow: &mut game::GameSystemFlow) {
+        use winit::event::ElementState;
+        use winit::keyboard::KeyCode;
+
+        if let winit::keyboard::PhysicalKey::Code(code) = input.physical_key {
+            match input.state {
+                ElementState::Pressed if !self.pressed.contains(&code) => {
+                    match code {
+                        KeyCode::Enter if !self.online => {
+                            let state = game::GameSystem::Versus(game::versus::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        KeyCode::Enter | KeyCode::Escape => {
+                            let state = game::GameSystem::Start(game::start::GameSystem::new());
+                            *flow = game::GameSystemFlow::To(state);
+                        }
+                        _ => {}
+                    }
+                    self.pressed.insert(code);
+                }
+                ElementState::Released => {
+                    self.pressed.remove(&code);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn update(&mut self, _clock: &dyn game::clock::Clock, _flow: &mut game::GameSystemFlow) {
+        // nothing
+    }
+
+    pub fn context(&'_ self) -> GameContext<'_> {
+        GameContext {
+            boards: &self.boards,
+            dimensions: &self.dimensions,
+            outcome: &self.outcome,
+            online: &self.online,
+        }
+    }
+}
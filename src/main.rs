@@ -2,23 +2,316 @@ use std::sync::Arc;
 
 mod consts;
 mod game;
+mod net;
 mod render;
 
 pub struct State {
+    clock: game::clock::ScaledClock<game::clock::RealClock>,
+    // `--events` prints every game event to stdout, for external tools to follow the game
+    log_events: bool,
     game_system: game::GameSystem,
     render_system: render::RenderSystem,
 }
 
 impl State {
     pub fn new(window: Arc<winit::window::Window>) -> Self {
-        let game_system = game::GameSystem::Start(game::start::GameSystem::new());
+        let clock = game::clock::ScaledClock::new(
+            game::clock::RealClock::new(),
+            Self::speed(std::env::args().skip(1)),
+        );
+        let log_events = std::env::args().any(|arg| arg == "--events");
+        let game_system = Self::initial_game_system(std::env::args().skip(1));
         let render_system = pollster::block_on(render::RenderSystem::new_async(window));
 
         Self {
+            clock,
+            log_events,
             game_system,
             render_system,
         }
     }
+
+    fn observe(
+        &self,
+        events: Vec<game::GameEvent>,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+    ) {
+        if events.contains(&game::GameEvent::Quit) {
+            event_loop.exit();
+        }
+        if self.log_events {
+            for event in events {
+                println!("{:?}", event);
+            }
+        }
+    }
+
+    // F7 halves and F8 doubles the speed of the game clock
+    fn check_and_change_speed(&mut self, event: &winit::event::KeyEvent) {
+        use winit::keyboard::{KeyCode, PhysicalKey};
+
+        if event.state != winit::event::ElementState::Pressed || event.repeat {
+            return;
+        }
+
+        let scale = match event.physical_key {
+            PhysicalKey::Code(KeyCode::F7) => self.clock.scale() / 2.0,
+            PhysicalKey::Code(KeyCode::F8) => self.clock.scale() * 2.0,
+            _ => return,
+        };
+        self.clock
+            .set_scale(scale.clamp(consts::MIN_CLOCK_SCALE, consts::MAX_CLOCK_SCALE));
+        eprintln!("speed {}x", self.clock.scale());
+    }
+
+    // `--speed <scale>` runs the game clock that many times as fast, below 1 for slow motion
+    fn speed(mut args: impl Iterator<Item = String>) -> f64 {
+        let mut speed = 1.0;
+        while let Some(arg) = args.next() {
+            if arg == "--speed" {
+                match args.next().and_then(|n| n.parse::<f64>().ok()) {
+                    Some(scale) if 0.0 < scale => {
+                        speed = scale.clamp(consts::MIN_CLOCK_SCALE, consts::MAX_CLOCK_SCALE);
+                    }
+                    _ => eprintln!("invalid speed"),
+                }
+            }
+        }
+        speed
+    }
+
+    // the value after a flag, reported when it is missing or doesn't parse
+    fn parse_arg<T: std::str::FromStr>(name: &str, value: Option<String>) -> Option<T> {
+        let Some(value) = value else {
+            eprintln!("missing {}", name);
+            return None;
+        };
+        let parsed = value.parse().ok();
+        if parsed.is_none() {
+            eprintln!("invalid {}: {}", name, value);
+        }
+        parsed
+    }
+
+    // the command line flags, besides --speed and --events read before the window opens
+    //   --host <port>                  hosts an online match
+    //   --connect <addr>               joins an online match
+    //   --rollback                     hosts with rollback netcode instead of lockstep
+    //   --input-delay <n>              frames of input delay under rollback
+    //   --max-rollback <n>             frames rollback may rewind
+    //   --bots <n>                     a battle royale against that many bots
+    //   --cheese <lines>               a cheese race clearing that many garbage lines
+    //   --messiness <percent>          how often the cheese holes move
+    //   --width <n>                    columns of the board
+    //   --height <n>                   visible rows of the board
+    //   --pieces <name or path>        a built-in piece set or one read from a file
+    //   --rotation <srs|srs+|ars|nrs>  the rotation system
+    //   --top-out <block,lock,partial> only the listed top out rules
+    //   --classic <level>              the NES ruleset from that level
+    //   --puzzles <path>               the puzzle list with the pack read from that file
+    //   --fumen <data>                 the editor on the first page of a fumen
+    //   --board <path>                 a plain text board read from a file
+    //   --script <path>                the mode a Rhai script describes
+    fn initial_game_system(mut args: impl Iterator<Item = String>) -> game::GameSystem {
+        let mut host = None::<u16>;
+        let mut connect = None;
+        let mut rollback = None::<net::rollback::Config>;
+        let mut bots = None::<usize>;
+        let mut cheese = None::<i32>;
+        let mut messiness = None::<u32>;
+        let mut width = None::<i32>;
+        let mut height = None::<i32>;
+        let mut pieces = None;
+        let mut rotation = None;
+        let mut top_out = None;
+        let mut classic = None::<u32>;
+        let mut puzzles = None;
+        let mut fumen = None;
+        let mut board = None;
+        let mut script = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--host" => host = Self::parse_arg("port", args.next()),
+                "--connect" => connect = args.next(),
+                "--rollback" => {
+                    rollback.get_or_insert_with(Default::default);
+                }
+                "--input-delay" => {
+                    let config = rollback.get_or_insert_with(Default::default);
+                    if let Some(input_delay) = Self::parse_arg("input delay", args.next()) {
+                        config.input_delay = consts::NET_MAX_INPUT_DELAY.min(input_delay);
+                    }
+                }
+                "--max-rollback" => {
+                    let config = rollback.get_or_insert_with(Default::default);
+                    if let Some(max_rollback) = Self::parse_arg("max rollback", args.next()) {
+                        config.max_rollback = consts::NET_MAX_ROLLBACK.min(max_rollback);
+                    }
+                }
+                "--bots" => bots = Self::parse_arg("number of bots", args.next()),
+                "--cheese" => cheese = Self::parse_arg("number of lines", args.next()),
+                "--messiness" => messiness = Self::parse_arg("messiness", args.next()),
+                "--width" => width = Self::parse_arg("width", args.next()),
+                "--height" => height = Self::parse_arg("height", args.next()),
+                "--pieces" => pieces = args.next(),
+                "--rotation" => rotation = args.next(),
+                "--top-out" => top_out = args.next(),
+                "--classic" => classic = Self::parse_arg("level", args.next()),
+                "--puzzles" => puzzles = args.next(),
+                "--fumen" => fumen = args.next(),
+                "--board" => board = args.next(),
+                "--script" => script = args.next(),
+                "--speed" => {
+                    args.next();
+                }
+                "--events" => {}
+                _ => eprintln!("unknown argument: {}", arg),
+            }
+        }
+
+        let mut config = game::playing::Config {
+            dimensions: consts::Dimensions::new(
+                width
+                    .unwrap_or(consts::DEFAULT_DIMENSIONS.width)
+                    .max(consts::MIN_BOARD_WIDTH),
+                height
+                    .unwrap_or(consts::DEFAULT_DIMENSIONS.visible_height)
+                    .max(consts::MIN_BOARD_HEIGHT),
+            ),
+            replay: true,
+            ..Default::default()
+        };
+        if let Some(name_or_path) = pieces.as_ref() {
+            match game::pieces::PieceSet::find(name_or_path) {
+                Ok(piece_set) => config.piece_set = piece_set,
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+        if let Some(name) = rotation.as_ref() {
+            match game::rotation::find(name) {
+                Some(rotation_system) => config.rotation_system = rotation_system,
+                None => eprintln!("unknown rotation system: {}", name),
+            }
+        }
+        let min_width = config.min_width();
+        if config.dimensions.width < min_width {
+            eprintln!(
+                "board width {} is too narrow for the {} pieces, using {}",
+                config.dimensions.width, config.piece_set.name, min_width
+            );
+            config.dimensions =
+                consts::Dimensions::new(min_width, config.dimensions.visible_height);
+        }
+        if let Some(rules) = top_out.as_ref() {
+            config.top_out = game::playing::TopOutRules {
+                block_out: false,
+                lock_out: false,
+                partial_lock_out: false,
+            };
+            for rule in rules.split(',') {
+                match rule {
+                    "block" => config.top_out.block_out = true,
+                    "lock" => config.top_out.lock_out = true,
+                    "partial" => config.top_out.partial_lock_out = true,
+                    _ => eprintln!("unknown top out rule: {}", rule),
+                }
+            }
+        }
+
+        let netcode = match rollback {
+            Some(config) => net::Netcode::Rollback(config),
+            None => net::Netcode::Lockstep,
+        };
+
+        if let Some(port) = host {
+            return game::GameSystem::Lobby(game::lobby::GameSystem::host(port, netcode));
+        }
+        if let Some(addr) = connect {
+            return game::GameSystem::Lobby(game::lobby::GameSystem::connect(&addr));
+        }
+        if let Some(bots) = bots {
+            return game::GameSystem::Royale(game::royale::GameSystem::new(bots));
+        }
+        if cheese.is_some() || messiness.is_some() {
+            let lines = cheese.unwrap_or(consts::CHEESE_DEFAULT_LINES);
+            let placement = match messiness {
+                Some(percent) => game::garbage::HolePlacement::Messiness(percent),
+                None => game::garbage::HolePlacement::Random,
+            };
+            return game::GameSystem::Cheese(game::cheese::GameSystem::new(
+                lines, placement, config,
+            ));
+        }
+        if let Some(level) = classic {
+            let config = game::playing::Config {
+                dimensions: config.dimensions,
+                ..game::playing::Config::classic(level.min(consts::NES_MAX_START_LEVEL))
+            };
+            return game::GameSystem::Playing(game::playing::GameSystem::with_config(
+                game::playing::DEFAULT_BINDINGS,
+                rand::random(),
+                config,
+            ));
+        }
+        if let Some(path) = puzzles {
+            match game::puzzles::PuzzlePack::load(&path) {
+                Ok(pack) => {
+                    return game::GameSystem::PuzzleSelect(
+                        game::puzzle_select::GameSystem::with_pack(pack),
+                    );
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+        if let Some(data) = fumen {
+            match game::fumen::decode(&data) {
+                Ok(pages) => {
+                    return game::GameSystem::Editor(game::editor::GameSystem::with_page(
+                        config, &pages[0],
+                    ));
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+        if let Some(path) = board {
+            let blocks = std::fs::read_to_string(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|text| game::board_text::parse(&text).map_err(|error| error.to_string()));
+            match blocks {
+                Ok(blocks) => {
+                    let mut system = game::playing::GameSystem::with_config(
+                        game::playing::DEFAULT_BINDINGS,
+                        rand::random(),
+                        config,
+                    );
+                    system.set_blocks(blocks);
+                    return game::GameSystem::Playing(system);
+                }
+                Err(error) => eprintln!("failed to read board {}: {}", path, error),
+            }
+        }
+        if let Some(path) = script {
+            match game::scripted::GameSystem::new(&path, config.clone()) {
+                Ok(system) => return game::GameSystem::Scripted(system),
+                Err(error) => eprintln!("failed to load script {}: {}", path, error),
+            }
+        }
+        if width.is_some()
+            || height.is_some()
+            || pieces.is_some()
+            || rotation.is_some()
+            || top_out.is_some()
+        {
+            return game::GameSystem::Playing(game::playing::GameSystem::with_config(
+                game::playing::DEFAULT_BINDINGS,
+                rand::random(),
+                config,
+            ));
+        }
+
+        game::GameSystem::Start(game::start::GameSystem::new())
+    }
 }
 
 pub struct App {
@@ -46,7 +339,7 @@ impl winit::application::ApplicationHandler for App {
 
     fn new_events(
         &mut self,
-        _event_loop: &winit::event_loop::ActiveEventLoop,
+        event_loop: &winit::event_loop::ActiveEventLoop,
         cause: winit::event::StartCause,
     ) {
         let Some(state) = &mut self.state else {
@@ -54,7 +347,8 @@ impl winit::application::ApplicationHandler for App {
         };
 
         if cause == winit::event::StartCause::Poll {
-            state.game_system.update();
+            let events = state.game_system.update(&state.clock);
+            state.observe(events, event_loop);
         }
     }
 
@@ -84,7 +378,27 @@ impl winit::application::ApplicationHandler for App {
                 state.render_system.resize(new_size);
             }
             winit::event::WindowEvent::KeyboardInput { event, .. } => {
-                state.game_system.input(&event);
+                state.check_and_change_speed(&event);
+                let events = state.game_system.input(&event);
+                state.observe(events, event_loop);
+            }
+            winit::event::WindowEvent::CursorMoved { position, .. } => {
+                let (x, y) = state.render_system.to_board(position);
+                let events = state
+                    .game_system
+                    .mouse_input(&game::MouseInput::Moved { x, y });
+                state.observe(events, event_loop);
+            }
+            winit::event::WindowEvent::MouseInput {
+                state: button_state,
+                button,
+                ..
+            } => {
+                let events = state.game_system.mouse_input(&game::MouseInput::Button {
+                    button,
+                    state: button_state,
+                });
+                state.observe(events, event_loop);
             }
             _ => {}
         }
@@ -92,7 +406,9 @@ impl winit::application::ApplicationHandler for App {
 }
 
 fn main() {
-    let event_loop = winit::event_loop::EventLoop::with_user_event().build().unwrap();
+    let event_loop = winit::event_loop::EventLoop::with_user_event()
+        .build()
+        .unwrap();
     let mut app = App::new();
     event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
     event_loop.run_app(&mut app).unwrap();
@@ -0,0 +1,259 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+use crate::game;
+use crate::game::playing::Action;
+use crate::net::protocol::Confirmation;
+use crate::net::{Event, Message, Status, Transport};
+use crate::{consts, net};
+
+const HISTORY_LEN: usize = 256;
+
+struct Record {
+    tick: u32,
+    checksum: u64,
+    garbage: [i32; 2],
+}
+
+// the simulation only advances once both players' inputs for a tick are known
+pub struct Session<T: Transport> {
+    transport: T,
+    local: usize,
+    last_received: Instant,
+
+    boards: [game::playing::GameSystem; 2],
+    tick: u32,
+
+    local_actions: Vec<Action>,
+    inputs: [BTreeMap<u32, Vec<Action>>; 2],
+    history: VecDeque<Record>,
+    remote_confirmations: VecDeque<Confirmation>,
+
+    disconnected: bool,
+}
+
+impl<T: Transport> Session<T> {
+    pub fn new(transport: T, local: usize, seed: u64) -> Self {
+        // nobody can send inputs for the ticks hidden behind the input delay
+        let inputs = [(); 2].map(|_| {
+            (0..consts::NET_INPUT_DELAY)
+                .map(|tick| (tick, vec![]))
+                .collect::<BTreeMap<_, _>>()
+        });
+
+        Self {
+            transport,
+            local,
+            last_received: Instant::now(),
+
+            boards: net::new_boards(seed),
+            tick: 0,
+
+            local_actions: vec![],
+            inputs,
+            history: VecDeque::new(),
+            remote_confirmations: VecDeque::new(),
+
+            disconnected: false,
+        }
+    }
+
+    pub fn add_local_action(&mut self, action: Action) {
+        self.local_actions.push(action);
+    }
+
+    pub fn advance_frame(&mut self) -> Status {
+        let remote = 1 - self.local;
+
+        while let Some(event) = self.transport.poll().filter(|_| !self.disconnected) {
+            match event {
+                Event::Message(Message::Tick {
+                    tick,
+                    actions,
+                    confirmed,
+                }) => {
+                    // the peer runs at most a tick past the inputs sent to it, an input delay
+                    // ahead, and sends its own an input delay further
+                    if self.tick + consts::NET_INPUT_DELAY * 2 + 1 < tick {
+                        self.disconnected = true;
+                        continue;
+                    }
+                    // ticks already simulated would never be taken out again
+                    if self.tick <= tick {
+                        self.inputs[remote].insert(tick, actions);
+                    }
+                    self.remote_confirmations.extend(confirmed);
+                    self.last_received = Instant::now();
+                }
+                // inputs received before the disconnect may still finish the game
+                Event::Message(Message::Bye) | Event::Disconnected => {
+                    self.disconnected = true;
+                }
+                Event::Message(_) => {}
+            }
+        }
+
+        if !self.check_remote_confirmations() {
+            return Status::Finished(game::versus_end::Outcome::Desynced);
+        }
+
+        let send_tick = self.tick + consts::NET_INPUT_DELAY;
+        if !self.inputs[self.local].contains_key(&send_tick) {
+            let actions = std::mem::take(&mut self.local_actions);
+            let confirmed = self.history.back().map(|record| Confirmation {
+                tick: record.tick,
+                checksum: record.checksum,
+                garbage: record.garbage[self.local],
+            });
+            let message = Message::Tick {
+                tick: send_tick,
+                actions: actions.clone(),
+                confirmed,
+            };
+            if self.transport.send(&message).is_err() {
+                self.disconnected = true;
+            }
+            self.inputs[self.local].insert(send_tick, actions);
+        }
+
+        if !self.inputs[remote].contains_key(&self.tick) {
+            if self.disconnected || consts::NET_TIMEOUT < self.last_received.elapsed() {
+                return Status::Finished(game::versus_end::Outcome::Disconnected);
+            }
+            return Status::Stalled;
+        }
+
+        let inputs = [0, 1].map(|index| self.inputs[index].remove(&self.tick).unwrap_or_default());
+        let garbage = match net::simulate_frame(&mut self.boards, inputs) {
+            Ok(garbage) => garbage,
+            Err(loser) => return Status::Finished(game::versus_end::Outcome::Winner(1 - loser)),
+        };
+
+        self.history.push_back(Record {
+            tick: self.tick,
+            checksum: net::checksum(&self.boards),
+            garbage,
+        });
+        if HISTORY_LEN < self.history.len() {
+            self.history.pop_front();
+        }
+
+        self.tick += 1;
+
+        Status::Running
+    }
+
+    // compares the remote state against the local record of the same tick
+    fn check_remote_confirmations(&mut self) -> bool {
+        let remote = 1 - self.local;
+
+        while let Some(confirmed) = self.remote_confirmations.front() {
+            if self.tick <= confirmed.tick {
+                break;
+            }
+
+            let record = self
+                .history
+                .iter()
+                .find(|record| record.tick == confirmed.tick);
+            if let Some(record) = record {
+                if record.checksum != confirmed.checksum
+                    || record.garbage[remote] != confirmed.garbage
+                {
+                    return false;
+                }
+            }
+
+            self.remote_confirmations.pop_front();
+        }
+
+        true
+    }
+
+    pub fn boards(&self) -> &[game::playing::GameSystem; 2] {
+        &self.boards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::loopback;
+
+    #[test]
+    fn stays_in_sync_with_latency() {
+        let (first, second, link) = loopback::pair(5, 0, 0.0, 7);
+        let mut sessions = [Session::new(first, 0, 42), Session::new(second, 1, 42)];
+
+        for frame in 0..240 {
+            link.borrow_mut().advance();
+            for (index, session) in sessions.iter_mut().enumerate() {
+                // the first player's game hangs for a while, letting the other run ahead of it
+                if index == 0 && (100..120).contains(&frame) {
+                    continue;
+                }
+                if frame % 30 == 0 {
+                    session.add_local_action(Action::HardDrop);
+                }
+                let status = session.advance_frame();
+                assert!(matches!(status, Status::Running | Status::Stalled));
+            }
+        }
+
+        assert!(0 < sessions[0].tick);
+        let common = sessions[0].tick.min(sessions[1].tick);
+        let checksums = sessions.each_ref().map(|session| {
+            session
+                .history
+                .iter()
+                .find(|record| record.tick + 1 == common)
+                .map(|record| record.checksum)
+        });
+        assert_eq!(checksums[0], checksums[1]);
+    }
+
+    #[test]
+    fn ticks_outside_the_window_are_refused() {
+        let (first, mut second, link) = loopback::pair(0, 0, 0.0, 7);
+        let mut session = Session::new(first, 0, 42);
+
+        // an old tick is dropped instead of being kept forever
+        for tick in [0, 1, 2, 3] {
+            second
+                .send(&Message::Tick {
+                    tick,
+                    actions: vec![],
+                    confirmed: None,
+                })
+                .unwrap();
+        }
+        link.borrow_mut().advance();
+        for _ in 0..4 {
+            assert!(matches!(session.advance_frame(), Status::Running));
+        }
+        second
+            .send(&Message::Tick {
+                tick: 0,
+                actions: vec![],
+                confirmed: None,
+            })
+            .unwrap();
+        link.borrow_mut().advance();
+        assert!(matches!(session.advance_frame(), Status::Stalled));
+        assert!(session.inputs[1].is_empty());
+
+        second
+            .send(&Message::Tick {
+                tick: u32::MAX,
+                actions: vec![],
+                confirmed: None,
+            })
+            .unwrap();
+        link.borrow_mut().advance();
+        assert!(matches!(
+            session.advance_frame(),
+            Status::Finished(game::versus_end::Outcome::Disconnected)
+        ));
+        assert!(session.inputs[1].is_empty());
+    }
+}
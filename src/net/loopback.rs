@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use rand::prelude::*;
+
+use crate::net::{Event, Message, Transport};
+
+// an in-memory link where time is counted in frames, so latency and loss are reproducible
+pub struct Link {
+    now: u64,
+    latency: u64,
+    jitter: u64,
+    loss: f64,
+    rng: StdRng,
+    queues: [VecDeque<(u64, Vec<u8>)>; 2],
+}
+
+impl Link {
+    pub fn advance(&mut self) {
+        self.now += 1;
+    }
+}
+
+pub struct LoopbackTransport {
+    link: Rc<RefCell<Link>>,
+    side: usize,
+}
+
+pub fn pair(
+    latency: u64,
+    jitter: u64,
+    loss: f64,
+    seed: u64,
+) -> (LoopbackTransport, LoopbackTransport, Rc<RefCell<Link>>) {
+    let link = Rc::new(RefCell::new(Link {
+        now: 0,
+        latency,
+        jitter,
+        loss,
+        rng: StdRng::seed_from_u64(seed),
+        queues: [VecDeque::new(), VecDeque::new()],
+    }));
+
+    (
+        LoopbackTransport {
+            link: link.clone(),
+            side: 0,
+        },
+        LoopbackTransport {
+            link: link.clone(),
+            side: 1,
+        },
+        link,
+    )
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, message: &Message) -> std::io::Result<()> {
+        let mut link = self.link.borrow_mut();
+        let Link {
+            now,
+            latency,
+            jitter,
+            loss,
+            ..
+        } = *link;
+
+        if link.rng.random_bool(loss) {
+            return Ok(());
+        }
+
+        let bytes = message
+            .encode()
+            .map_err(|error| std::io::Error::other(error.to_string()))?;
+        let deliver_at = now + latency + link.rng.random_range(0..=jitter);
+        link.queues[1 - self.side].push_back((deliver_at, bytes));
+
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Option<Event> {
+        let mut link = self.link.borrow_mut();
+        let now = link.now;
+
+        let queue = &mut link.queues[self.side];
+        let position = queue
+            .iter()
+            .position(|(deliver_at, _)| *deliver_at <= now)?;
+        let (_, bytes) = queue.remove(position)?;
+
+        Some(Event::Message(Message::decode(&bytes).unwrap()))
+    }
+}
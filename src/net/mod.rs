@@ -0,0 +1,208 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+
+use crate::game;
+use crate::game::playing::Action;
+
+pub mod lockstep;
+#[cfg(test)]
+mod loopback;
+pub mod protocol;
+pub mod rollback;
+
+pub use protocol::Message;
+
+pub enum Event {
+    Message(Message),
+    Disconnected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Netcode {
+    Lockstep,
+    Rollback(rollback::Config),
+}
+
+pub trait Transport {
+    fn send(&mut self, message: &Message) -> std::io::Result<()>;
+    fn poll(&mut self) -> Option<Event>;
+}
+
+pub enum Status {
+    Running,
+    Stalled,
+    Finished(game::versus_end::Outcome),
+}
+
+pub enum Session<T: Transport> {
+    Lockstep(lockstep::Session<T>),
+    Rollback(rollback::Session<T>),
+}
+
+impl<T: Transport> Session<T> {
+    pub fn new(netcode: Netcode, transport: T, local: usize, seed: u64) -> Self {
+        match netcode {
+            Netcode::Lockstep => Session::Lockstep(lockstep::Session::new(transport, local, seed)),
+            Netcode::Rollback(config) => {
+                Session::Rollback(rollback::Session::new(config, transport, local, seed))
+            }
+        }
+    }
+
+    pub fn add_local_action(&mut self, action: Action) {
+        match self {
+            Session::Lockstep(session) => session.add_local_action(action),
+            Session::Rollback(session) => session.add_local_action(action),
+        }
+    }
+
+    pub fn advance_frame(&mut self) -> Status {
+        match self {
+            Session::Lockstep(session) => session.advance_frame(),
+            Session::Rollback(session) => session.advance_frame(),
+        }
+    }
+
+    pub fn boards(&self) -> &[game::playing::GameSystem; 2] {
+        match self {
+            Session::Lockstep(session) => session.boards(),
+            Session::Rollback(session) => session.boards(),
+        }
+    }
+}
+
+pub fn new_boards(seed: u64) -> [game::playing::GameSystem; 2] {
+    use game::playing::{GameSystem, DEFAULT_BINDINGS};
+
+    [
+        GameSystem::with_bindings(DEFAULT_BINDINGS, seed),
+        GameSystem::with_bindings(DEFAULT_BINDINGS, seed.wrapping_add(1)),
+    ]
+}
+
+// both peers run the exact same frame on both boards, so the order of every step matters
+pub fn simulate_frame(
+    boards: &mut [game::playing::GameSystem; 2],
+    inputs: [Vec<Action>; 2],
+) -> Result<[i32; 2], usize> {
+    for (index, actions) in inputs.into_iter().enumerate() {
+        let mut board_flow = game::GameSystemFlow::Default;
+        for action in actions {
+            boards[index].action(action, &mut board_flow);
+        }
+        boards[index].step(crate::consts::NET_TICK_INTERVAL, &mut board_flow);
+
+        if let game::GameSystemFlow::To(_) = board_flow {
+            return Err(index);
+        }
+    }
+
+    let [first, second] = boards;
+    let garbage = [
+        first.take_outgoing_garbage(),
+        second.take_outgoing_garbage(),
+    ];
+    second.receive_garbage(garbage[0]);
+    first.receive_garbage(garbage[1]);
+
+    Ok(garbage)
+}
+
+pub fn checksum(boards: &[game::playing::GameSystem; 2]) -> u64 {
+    boards
+        .iter()
+        .fold(0u64, |acc, board| acc.rotate_left(1) ^ board.checksum())
+}
+
+pub struct Connection {
+    stream: TcpStream,
+    receiver: mpsc::Receiver<Event>,
+}
+
+impl Connection {
+    pub fn connect(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or(std::io::ErrorKind::AddrNotAvailable)?;
+        let stream = TcpStream::connect_timeout(&addr, crate::consts::NET_TIMEOUT)?;
+        Self::new(stream)
+    }
+
+    pub fn accept(listener: &TcpListener) -> std::io::Result<Option<Self>> {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                Self::new(stream).map(Some)
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nodelay(true)?;
+
+        let (sender, receiver) = mpsc::channel();
+
+        // frames are read on their own thread so that the game loop never blocks on the socket
+        let mut reader = stream.try_clone()?;
+        std::thread::spawn(move || loop {
+            let mut len = [0; 4];
+            let mut payload = vec![];
+            // a length past the limit is refused before anything is allocated for it
+            let received = reader.read_exact(&mut len).and_then(|_| {
+                let len = u32::from_be_bytes(len) as usize;
+                if crate::consts::NET_MAX_FRAME_LEN < len {
+                    eprintln!("message of {} bytes is too long", len);
+                    return Err(std::io::ErrorKind::InvalidData.into());
+                }
+                payload.resize(len, 0);
+                reader.read_exact(&mut payload)
+            });
+
+            let event = match received.map(|_| Message::decode(&payload)) {
+                Ok(Ok(message)) => Event::Message(message),
+                Ok(Err(err)) => {
+                    eprintln!("failed to decode message: {}", err);
+                    Event::Disconnected
+                }
+                Err(_) => Event::Disconnected,
+            };
+
+            let disconnected = matches!(event, Event::Disconnected);
+            if sender.send(event).is_err() || disconnected {
+                break;
+            }
+        });
+
+        Ok(Self { stream, receiver })
+    }
+}
+
+impl Transport for Connection {
+    fn send(&mut self, message: &Message) -> std::io::Result<()> {
+        let payload = message
+            .encode()
+            .map_err(|error| std::io::Error::other(error.to_string()))?;
+        let mut frame = (payload.len() as u32).to_be_bytes().to_vec();
+        frame.extend(payload);
+        self.stream.write_all(&frame)
+    }
+
+    fn poll(&mut self) -> Option<Event> {
+        match self.receiver.try_recv() {
+            Ok(event) => Some(event),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Event::Disconnected),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.send(&Message::Bye);
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::consts;
+use crate::game::playing::Action;
+use crate::net::{rollback, Netcode};
+
+// bumped whenever the layout of a message changes
+pub const PROTOCOL_VERSION: u16 = 2;
+
+const KIND_HELLO: u8 = 0;
+const KIND_WELCOME: u8 = 1;
+const KIND_TICK: u8 = 2;
+const KIND_BYE: u8 = 3;
+const KIND_INPUTS: u8 = 4;
+
+const NETCODE_LOCKSTEP: u8 = 0;
+const NETCODE_ROLLBACK: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Hello {
+        version: u16,
+    },
+    Welcome {
+        version: u16,
+        seed: u64,
+        netcode: Netcode,
+    },
+    Tick {
+        tick: u32,
+        actions: Vec<Action>,
+        // the state of the sender after its last simulated tick
+        confirmed: Option<Confirmation>,
+    },
+    // every local input the peer has not acknowledged yet, starting at `start`
+    Inputs {
+        start: u32,
+        inputs: Vec<Vec<Action>>,
+        ack: u32,
+        checksum: Option<(u32, u64)>,
+    },
+    Bye,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Confirmation {
+    pub tick: u32,
+    pub checksum: u64,
+    pub garbage: i32,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    UnexpectedEnd,
+    UnknownKind(u8),
+    UnknownAction(u8),
+    UnknownNetcode(u8),
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of message"),
+            DecodeError::UnknownKind(kind) => write!(f, "unknown message kind {}", kind),
+            DecodeError::UnknownAction(action) => write!(f, "unknown action {}", action),
+            DecodeError::UnknownNetcode(netcode) => write!(f, "unknown netcode {}", netcode),
+        }
+    }
+}
+
+// a message that doesn't fit its length fields or the frame limit, refused instead of truncated
+#[derive(Debug)]
+pub enum EncodeError {
+    TooManyActions(usize),
+    TooManyInputs(usize),
+    FrameTooLong(usize),
+}
+
+impl std::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::TooManyActions(len) => write!(f, "{} actions in one frame", len),
+            EncodeError::TooManyInputs(len) => write!(f, "{} frames of inputs at once", len),
+            EncodeError::FrameTooLong(len) => write!(f, "message of {} bytes is too long", len),
+        }
+    }
+}
+
+impl Message {
+    pub fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut bytes = vec![];
+
+        match self {
+            Message::Hello { version } => {
+                bytes.push(KIND_HELLO);
+                bytes.extend(version.to_be_bytes());
+            }
+            Message::Welcome {
+                version,
+                seed,
+                netcode,
+            } => {
+                bytes.push(KIND_WELCOME);
+                bytes.extend(version.to_be_bytes());
+                bytes.extend(seed.to_be_bytes());
+                match netcode {
+                    Netcode::Lockstep => bytes.push(NETCODE_LOCKSTEP),
+                    Netcode::Rollback(config) => {
+                        bytes.push(NETCODE_ROLLBACK);
+                        bytes.extend(config.input_delay.to_be_bytes());
+                        bytes.extend(config.max_rollback.to_be_bytes());
+                    }
+                }
+            }
+            Message::Tick {
+                tick,
+                actions,
+                confirmed,
+            } => {
+                bytes.push(KIND_TICK);
+                bytes.extend(tick.to_be_bytes());
+                encode_actions(&mut bytes, actions)?;
+                match confirmed {
+                    Some(confirmed) => {
+                        bytes.push(1);
+                        bytes.extend(confirmed.tick.to_be_bytes());
+                        bytes.extend(confirmed.checksum.to_be_bytes());
+                        bytes.extend(confirmed.garbage.to_be_bytes());
+                    }
+                    None => bytes.push(0),
+                }
+            }
+            Message::Inputs {
+                start,
+                inputs,
+                ack,
+                checksum,
+            } => {
+                bytes.push(KIND_INPUTS);
+                bytes.extend(start.to_be_bytes());
+                let len = u16::try_from(inputs.len())
+                    .map_err(|_| EncodeError::TooManyInputs(inputs.len()))?;
+                bytes.extend(len.to_be_bytes());
+                for actions in inputs.iter() {
+                    encode_actions(&mut bytes, actions)?;
+                }
+                bytes.extend(ack.to_be_bytes());
+                match checksum {
+                    Some((frame, checksum)) => {
+                        bytes.push(1);
+                        bytes.extend(frame.to_be_bytes());
+                        bytes.extend(checksum.to_be_bytes());
+                    }
+                    None => bytes.push(0),
+                }
+            }
+            Message::Bye => {
+                bytes.push(KIND_BYE);
+            }
+        }
+
+        if consts::NET_MAX_FRAME_LEN < bytes.len() {
+            return Err(EncodeError::FrameTooLong(bytes.len()));
+        }
+        Ok(bytes)
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+
+        match reader.take::<1>()?[0] {
+            KIND_HELLO => Ok(Message::Hello {
+                version: u16::from_be_bytes(reader.take()?),
+            }),
+            KIND_WELCOME => {
+                let version = u16::from_be_bytes(reader.take()?);
+                let seed = u64::from_be_bytes(reader.take()?);
+
+                let netcode = match reader.take::<1>()?[0] {
+                    NETCODE_LOCKSTEP => Netcode::Lockstep,
+                    NETCODE_ROLLBACK => Netcode::Rollback(rollback::Config {
+                        input_delay: u32::from_be_bytes(reader.take()?),
+                        max_rollback: u32::from_be_bytes(reader.take()?),
+                    }),
+                    netcode => return Err(DecodeError::UnknownNetcode(netcode)),
+                };
+
+                Ok(Message::Welcome {
+                    version,
+                    seed,
+                    netcode,
+                })
+            }
+            KIND_TICK => {
+                let tick = u32::from_be_bytes(reader.take()?);
+                let actions = reader.take_actions()?;
+
+                let confirmed = match reader.take::<1>()?[0] {
+                    0 => None,
+                    _ => Some(Confirmation {
+                        tick: u32::from_be_bytes(reader.take()?),
+                        checksum: u64::from_be_bytes(reader.take()?),
+                        garbage: i32::from_be_bytes(reader.take()?),
+                    }),
+                };
+
+                Ok(Message::Tick {
+                    tick,
+                    actions,
+                    confirmed,
+                })
+            }
+            KIND_INPUTS => {
+                let start = u32::from_be_bytes(reader.take()?);
+
+                let len = u16::from_be_bytes(reader.take()?);
+                let inputs = (0..len)
+                    .map(|_| reader.take_actions())
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let ack = u32::from_be_bytes(reader.take()?);
+
+                let checksum = match reader.take::<1>()?[0] {
+                    0 => None,
+                    _ => Some((
+                        u32::from_be_bytes(reader.take()?),
+                        u64::from_be_bytes(reader.take()?),
+                    )),
+                };
+
+                Ok(Message::Inputs {
+                    start,
+                    inputs,
+                    ack,
+                    checksum,
+                })
+            }
+            KIND_BYE => Ok(Message::Bye),
+            kind => Err(DecodeError::UnknownKind(kind)),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let (head, tail) = self
+            .bytes
+            .split_first_chunk::<N>()
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        self.bytes = tail;
+        Ok(*head)
+    }
+
+    fn take_actions(&mut self) -> Result<Vec<Action>, DecodeError> {
+        let len = self.take::<1>()?[0];
+        (0..len)
+            .map(|_| decode_action(self.take::<1>()?[0]))
+            .collect()
+    }
+}
+
+fn encode_actions(bytes: &mut Vec<u8>, actions: &[Action]) -> Result<(), EncodeError> {
+    let len =
+        u8::try_from(actions.len()).map_err(|_| EncodeError::TooManyActions(actions.len()))?;
+    bytes.push(len);
+    bytes.extend(actions.iter().map(|action| encode_action(*action)));
+    Ok(())
+}
+
+fn encode_action(action: Action) -> u8 {
+    match action {
+        Action::MoveLeft => 0,
+        Action::MoveRight => 1,
+        Action::SoftDrop => 2,
+        Action::HardDrop => 3,
+        Action::RotateClockwise => 4,
+        Action::RotateCounterClockwise => 5,
+        Action::RotateFlip => 6,
+        Action::Hold => 7,
+    }
+}
+
+fn decode_action(byte: u8) -> Result<Action, DecodeError> {
+    match byte {
+        0 => Ok(Action::MoveLeft),
+        1 => Ok(Action::MoveRight),
+        2 => Ok(Action::SoftDrop),
+        3 => Ok(Action::HardDrop),
+        4 => Ok(Action::RotateClockwise),
+        5 => Ok(Action::RotateCounterClockwise),
+        6 => Ok(Action::RotateFlip),
+        7 => Ok(Action::Hold),
+        byte => Err(DecodeError::UnknownAction(byte)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_survive_a_round_trip() {
+        let message = Message::Inputs {
+            start: 7,
+            inputs: vec![vec![], vec![Action::HardDrop, Action::Hold]],
+            ack: 5,
+            checksum: Some((4, 0xdead_beef)),
+        };
+        let bytes = message.encode().unwrap();
+        assert_eq!(Message::decode(&bytes).unwrap(), message);
+    }
+
+    #[test]
+    fn oversized_batches_fail_to_encode() {
+        let message = Message::Tick {
+            tick: 0,
+            actions: vec![Action::MoveLeft; 256],
+            confirmed: None,
+        };
+        assert!(matches!(
+            message.encode(),
+            Err(EncodeError::TooManyActions(256))
+        ));
+
+        let message = Message::Inputs {
+            start: 0,
+            inputs: vec![vec![Action::MoveLeft; 255]; 64],
+            ack: 0,
+            checksum: None,
+        };
+        assert!(matches!(
+            message.encode(),
+            Err(EncodeError::FrameTooLong(_))
+        ));
+    }
+}
@@ -0,0 +1,498 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::game;
+use crate::game::playing::Action;
+use crate::net::{Event, Message, Status, Transport};
+use crate::{consts, net};
+
+const CHECKSUM_HISTORY_LEN: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub input_delay: u32,
+    pub max_rollback: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input_delay: 2,
+            max_rollback: 8,
+        }
+    }
+}
+
+// the boards as they were right before `frame` was simulated
+struct Snapshot {
+    frame: u32,
+    boards: [game::playing::GameSystem; 2],
+}
+
+// remote inputs are predicted as idle, and the frames are simulated again from the last
+// snapshot whenever the real inputs turn out to be different
+pub struct Session<T: Transport> {
+    transport: T,
+    config: Config,
+    local: usize,
+    last_received: Instant,
+
+    boards: [game::playing::GameSystem; 2],
+    frame: u32,
+
+    local_actions: Vec<Action>,
+    inputs: [Vec<Option<Vec<Action>>>; 2],
+    snapshots: VecDeque<Snapshot>,
+
+    // every remote input before `confirmed` is known, and the remote knows ours before `acked`
+    confirmed: u32,
+    acked: u32,
+
+    checked: u32,
+    checksums: VecDeque<(u32, u64)>,
+    remote_checksums: VecDeque<(u32, u64)>,
+
+    topped_out: Option<(u32, usize)>,
+    outcome: Option<game::versus_end::Outcome>,
+    disconnected: bool,
+    rollbacks: u32,
+}
+
+impl<T: Transport> Session<T> {
+    pub fn new(config: Config, transport: T, local: usize, seed: u64) -> Self {
+        // nobody can send inputs for the frames hidden behind the input delay
+        let inputs = [(); 2].map(|_| vec![Some(vec![]); config.input_delay as usize]);
+
+        Self {
+            transport,
+            config,
+            local,
+            last_received: Instant::now(),
+
+            boards: net::new_boards(seed),
+            frame: 0,
+
+            local_actions: vec![],
+            inputs,
+            snapshots: VecDeque::new(),
+
+            confirmed: config.input_delay,
+            acked: config.input_delay,
+
+            checked: 0,
+            checksums: VecDeque::new(),
+            remote_checksums: VecDeque::new(),
+
+            topped_out: None,
+            outcome: None,
+            disconnected: false,
+            rollbacks: 0,
+        }
+    }
+
+    pub fn add_local_action(&mut self, action: Action) {
+        self.local_actions.push(action);
+    }
+
+    pub fn advance_frame(&mut self) -> Status {
+        // a finished session keeps resending its inputs so that a lossy peer can finish too
+        if let Some(outcome) = self.outcome {
+            self.receive();
+            self.send();
+            return Status::Finished(outcome);
+        }
+
+        if let Some(frame) = self.receive() {
+            self.rollback(frame);
+        }
+
+        self.record_checksums();
+
+        if !self.check_remote_checksums() {
+            return self.finish(game::versus_end::Outcome::Desynced);
+        }
+
+        if let Some((frame, loser)) = self.topped_out {
+            if frame < self.confirmed {
+                return self.finish(game::versus_end::Outcome::Winner(1 - loser));
+            }
+        }
+
+        if self.disconnected {
+            return self.finish(game::versus_end::Outcome::Disconnected);
+        }
+
+        let status = if self.topped_out.is_some()
+            || self.confirmed + self.config.max_rollback <= self.frame
+        {
+            if consts::NET_TIMEOUT < self.last_received.elapsed() {
+                return self.finish(game::versus_end::Outcome::Disconnected);
+            }
+            Status::Stalled
+        } else {
+            let actions = std::mem::take(&mut self.local_actions);
+            self.inputs[self.local].push(Some(actions));
+
+            self.simulate_frame();
+            self.record_checksums();
+
+            Status::Running
+        };
+
+        self.send();
+
+        status
+    }
+
+    fn finish(&mut self, outcome: game::versus_end::Outcome) -> Status {
+        self.outcome = Some(outcome);
+        self.send();
+        Status::Finished(outcome)
+    }
+
+    fn receive(&mut self) -> Option<u32> {
+        let remote = 1 - self.local;
+        let mut rollback_frame = None::<u32>;
+
+        while let Some(event) = self.transport.poll().filter(|_| !self.disconnected) {
+            match event {
+                Event::Message(Message::Inputs {
+                    start,
+                    inputs,
+                    ack,
+                    checksum,
+                }) => {
+                    // a peer playing by the rules never sends past this frame, so anything later
+                    // is treated as a disconnect before it can grow the input list
+                    let end = start as u64 + inputs.len() as u64;
+                    if self.last_remote_frame() as u64 + 1 < end {
+                        self.disconnected = true;
+                        continue;
+                    }
+
+                    for (frame, actions) in (start..).zip(inputs) {
+                        let index = frame as usize;
+                        if self.inputs[remote].len() <= index {
+                            self.inputs[remote].resize(index + 1, None);
+                        }
+                        if self.inputs[remote][index].is_some() {
+                            continue;
+                        }
+
+                        // only a frame that was simulated with a wrong prediction has to be redone
+                        if frame < self.frame && !actions.is_empty() {
+                            rollback_frame = Some(rollback_frame.map_or(frame, |f| f.min(frame)));
+                        }
+                        self.inputs[remote][index] = Some(actions);
+                    }
+
+                    while let Some(Some(_)) = self.inputs[remote].get(self.confirmed as usize) {
+                        self.confirmed += 1;
+                    }
+
+                    self.acked = self.acked.max(ack);
+                    self.remote_checksums.extend(checksum);
+                    self.last_received = Instant::now();
+                }
+                // inputs received before the disconnect may still finish the game
+                Event::Message(Message::Bye) | Event::Disconnected => {
+                    self.disconnected = true;
+                }
+                Event::Message(_) => {}
+            }
+        }
+
+        rollback_frame
+    }
+
+    // the remote runs at most max rollback frames past the inputs it has confirmed from us, which
+    // reach input delay frames past ours, and sends its inputs input delay frames further ahead
+    fn last_remote_frame(&self) -> u32 {
+        self.frame + self.config.max_rollback + self.config.input_delay * 2
+    }
+
+    fn rollback(&mut self, frame: u32) {
+        let Some(position) = self.snapshots.iter().position(|s| s.frame == frame) else {
+            return;
+        };
+
+        let target = self.frame;
+
+        self.snapshots.truncate(position + 1);
+        self.boards = self.snapshots.pop_back().unwrap().boards;
+        self.frame = frame;
+        if self
+            .topped_out
+            .is_some_and(|(topped_out, _)| frame <= topped_out)
+        {
+            self.topped_out = None;
+        }
+
+        while self.frame < target && self.topped_out.is_none() {
+            self.simulate_frame();
+        }
+
+        self.rollbacks += 1;
+    }
+
+    fn simulate_frame(&mut self) {
+        self.snapshots.push_back(Snapshot {
+            frame: self.frame,
+            boards: self.boards.clone(),
+        });
+
+        let inputs = [0, 1].map(|index| {
+            self.inputs[index]
+                .get(self.frame as usize)
+                .cloned()
+                .flatten()
+                .unwrap_or_default()
+        });
+
+        if let Err(loser) = net::simulate_frame(&mut self.boards, inputs) {
+            self.topped_out = Some((self.frame, loser));
+        }
+
+        self.frame += 1;
+    }
+
+    // a frame is final once every input up to it is confirmed, so its checksum can be compared
+    fn record_checksums(&mut self) {
+        while self.checked < self.confirmed.min(self.frame) {
+            let after = self.checked + 1;
+            let checksum = match self.snapshots.iter().find(|s| s.frame == after) {
+                Some(snapshot) => net::checksum(&snapshot.boards),
+                None if after == self.frame => net::checksum(&self.boards),
+                None => break,
+            };
+
+            self.checksums.push_back((self.checked, checksum));
+            if CHECKSUM_HISTORY_LEN < self.checksums.len() {
+                self.checksums.pop_front();
+            }
+
+            self.checked += 1;
+        }
+
+        while self
+            .snapshots
+            .front()
+            .is_some_and(|snapshot| snapshot.frame < self.confirmed.min(self.checked))
+        {
+            self.snapshots.pop_front();
+        }
+    }
+
+    fn check_remote_checksums(&mut self) -> bool {
+        while let Some((frame, checksum)) = self.remote_checksums.front().copied() {
+            if self.checked <= frame {
+                break;
+            }
+
+            let local = self.checksums.iter().find(|(f, _)| *f == frame);
+            if local.is_some_and(|(_, local)| *local != checksum) {
+                return false;
+            }
+
+            self.remote_checksums.pop_front();
+        }
+
+        true
+    }
+
+    fn send(&mut self) {
+        let start = self.acked.min(self.inputs[self.local].len() as u32);
+        let inputs = self.inputs[self.local][start as usize..]
+            .iter()
+            .map(|actions| actions.clone().unwrap_or_default())
+            .collect();
+
+        let message = Message::Inputs {
+            start,
+            inputs,
+            ack: self.confirmed,
+            checksum: self.checksums.back().copied(),
+        };
+        if self.transport.send(&message).is_err() {
+            self.disconnected = true;
+        }
+    }
+
+    pub fn boards(&self) -> &[game::playing::GameSystem; 2] {
+        &self.boards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::net::loopback;
+
+    const ACTIONS: &[Action] = &[
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::SoftDrop,
+        Action::HardDrop,
+        Action::RotateClockwise,
+        Action::RotateCounterClockwise,
+        Action::RotateFlip,
+        Action::Hold,
+    ];
+
+    struct Run {
+        sessions: [Session<loopback::LoopbackTransport>; 2],
+        outcomes: [Option<game::versus_end::Outcome>; 2],
+        stalls: [u32; 2],
+    }
+
+    fn run(config: Config, latency: u64, jitter: u64, loss: f64, frames: u32) -> Run {
+        let (first, second, link) = loopback::pair(latency, jitter, loss, 7);
+        let mut run = Run {
+            sessions: [
+                Session::new(config, first, 0, 42),
+                Session::new(config, second, 1, 42),
+            ],
+            outcomes: [None, None],
+            stalls: [0, 0],
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..frames {
+            link.borrow_mut().advance();
+
+            for index in 0..2 {
+                if run.outcomes[index].is_none() && rng.random_bool(0.2) {
+                    let action = *ACTIONS.choose(&mut rng).unwrap();
+                    run.sessions[index].add_local_action(action);
+                }
+
+                match run.sessions[index].advance_frame() {
+                    Status::Running => {}
+                    Status::Stalled => run.stalls[index] += 1,
+                    Status::Finished(outcome) => {
+                        run.outcomes[index].get_or_insert(outcome);
+                    }
+                }
+            }
+        }
+
+        run
+    }
+
+    fn assert_in_sync(run: &Run) {
+        let [first, second] = &run.sessions;
+
+        let mut compared = 0;
+        for (frame, checksum) in first.checksums.iter() {
+            if let Some((_, other)) = second.checksums.iter().find(|(f, _)| f == frame) {
+                assert_eq!(checksum, other, "checksums differ at frame {}", frame);
+                compared += 1;
+            }
+        }
+        assert!(0 < compared, "no confirmed frames were compared");
+
+        for outcome in run.outcomes.iter().flatten() {
+            assert!(!matches!(outcome, game::versus_end::Outcome::Desynced));
+        }
+        match &run.outcomes {
+            [None, None] => {}
+            [Some(game::versus_end::Outcome::Winner(first)), Some(game::versus_end::Outcome::Winner(second))] =>
+            {
+                assert_eq!(first, second)
+            }
+            _ => panic!("sessions finished differently"),
+        }
+    }
+
+    #[test]
+    fn stays_in_sync_without_latency() {
+        let run = run(Config::default(), 0, 0, 0.0, 600);
+
+        assert_in_sync(&run);
+        assert_eq!(run.stalls, [0, 0]);
+    }
+
+    #[test]
+    fn rolls_back_late_inputs() {
+        let run = run(Config::default(), 4, 0, 0.0, 600);
+
+        assert_in_sync(&run);
+        assert!(0 < run.sessions[0].rollbacks);
+        assert!(0 < run.sessions[1].rollbacks);
+    }
+
+    #[test]
+    fn stays_in_sync_with_jitter_and_packet_loss() {
+        let config = Config {
+            input_delay: 1,
+            max_rollback: 12,
+        };
+        let run = run(config, 3, 4, 0.25, 1200);
+
+        assert_in_sync(&run);
+    }
+
+    #[test]
+    fn stalls_beyond_max_rollback() {
+        let config = Config {
+            input_delay: 0,
+            max_rollback: 4,
+        };
+        let run = run(config, 10, 0, 0.0, 100);
+
+        assert_in_sync(&run);
+        assert!(0 < run.stalls[0]);
+        assert!(run.sessions[0].frame <= run.sessions[0].confirmed + config.max_rollback);
+    }
+
+    #[test]
+    fn inputs_far_past_the_window_disconnect() {
+        let (first, mut second, link) = loopback::pair(0, 0, 0.0, 7);
+        let mut session = Session::new(Config::default(), first, 0, 42);
+
+        second
+            .send(&Message::Inputs {
+                start: u32::MAX - 1,
+                inputs: vec![vec![]; 3],
+                ack: 0,
+                checksum: None,
+            })
+            .unwrap();
+        link.borrow_mut().advance();
+
+        assert!(matches!(
+            session.advance_frame(),
+            Status::Finished(game::versus_end::Outcome::Disconnected)
+        ));
+        assert!(session.inputs[1].len() <= Config::default().input_delay as usize);
+    }
+
+    #[test]
+    fn the_most_unacked_inputs_fit_in_a_frame() {
+        // the peer confirms at most a window and an input delay past what it acked, and this side
+        // runs a window and an input delay past that, each frame here pressing every action
+        let len = 2 * (consts::NET_MAX_ROLLBACK + consts::NET_MAX_INPUT_DELAY) as usize;
+        let message = Message::Inputs {
+            start: u32::MAX,
+            inputs: vec![ACTIONS.to_vec(); len],
+            ack: u32::MAX,
+            checksum: Some((u32::MAX, u64::MAX)),
+        };
+
+        assert!(message.encode().unwrap().len() <= consts::NET_MAX_FRAME_LEN);
+    }
+
+    #[test]
+    fn stalls_while_the_peer_is_silent() {
+        let run = run(Config::default(), 0, 0, 1.0, 60);
+
+        let config = Config::default();
+        assert_eq!(
+            run.sessions[0].frame,
+            config.input_delay + config.max_rollback
+        );
+        assert!(0 < run.stalls[0]);
+    }
+}
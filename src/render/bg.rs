@@ -31,12 +31,14 @@ pub struct Pipeline {
 
 impl Pipeline {
     #[rustfmt::skip]
-    const VERTICES: &[Vertex] = &[
-        Vertex { position: [-consts::VIEW_WIDTH * 0.5, -consts::VIEW_HEIGHT * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
-        Vertex { position: [ consts::VIEW_WIDTH * 0.5, -consts::VIEW_HEIGHT * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
-        Vertex { position: [ consts::VIEW_WIDTH * 0.5,  consts::VIEW_HEIGHT * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
-        Vertex { position: [-consts::VIEW_WIDTH * 0.5,  consts::VIEW_HEIGHT * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
-    ];
+    fn vertices(width: f32, height: f32) -> [Vertex; 4] {
+        [
+            Vertex { position: [-width * 0.5, -height * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
+            Vertex { position: [ width * 0.5, -height * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
+            Vertex { position: [ width * 0.5,  height * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
+            Vertex { position: [-width * 0.5,  height * 0.5, 0.0], color: consts::block_color::BG_DEFAULT },
+        ]
+    }
 
     const INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
 
@@ -47,8 +49,11 @@ impl Pipeline {
     ) -> Self {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
-            contents: bytemuck::cast_slice(Self::VERTICES),
-            usage: wgpu::BufferUsages::VERTEX,
+            contents: bytemuck::cast_slice(&Self::vertices(
+                consts::DEFAULT_DIMENSIONS.width as f32,
+                consts::DEFAULT_DIMENSIONS.visible_height as f32,
+            )),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -123,6 +128,7 @@ impl Pipeline {
         queue: &wgpu::Queue,
         target: &wgpu::TextureView,
         bind_group: &wgpu::BindGroup,
+        clear: bool,
     ) {
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
@@ -140,7 +146,11 @@ impl Pipeline {
                 resolve_target: None,
                 depth_slice: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(color),
+                    load: if clear {
+                        wgpu::LoadOp::Clear(color)
+                    } else {
+                        wgpu::LoadOp::Load
+                    },
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -160,4 +170,10 @@ impl Pipeline {
 
         queue.submit([encoder.finish()]);
     }
+
+    // the background covers the visible part of a board of the given size
+    pub fn set_size(&self, queue: &wgpu::Queue, width: f32, height: f32) {
+        let vertices = Self::vertices(width, height);
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
 }
@@ -50,18 +50,40 @@ impl Camera {
 pub struct Resource {
     camera: Camera,
     buffer: wgpu::Buffer,
-    pub bind_group_layout: wgpu::BindGroupLayout,
     pub bind_group: wgpu::BindGroup,
+    view_width: f32,
+    view_height: f32,
+    width: f32,
+    height: f32,
 }
 
 impl Resource {
-    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
-        let (clipping_width, clipping_height) = Self::get_contain_clipping(
-            consts::VIEW_WIDTH,
-            consts::VIEW_HEIGHT,
-            width as _,
-            height as _,
-        );
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        })
+    }
+
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let view_width = consts::DEFAULT_DIMENSIONS.width as f32;
+        let view_height = consts::DEFAULT_DIMENSIONS.visible_height as f32;
+        let (clipping_width, clipping_height) =
+            Self::get_contain_clipping(view_width, view_height, width as _, height as _);
         let camera = Camera {
             eye: Point3::new(0.0, 0.0, 10.0),
             target: -Vector3::unit_z(),
@@ -78,22 +100,8 @@ impl Resource {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: None,
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-        });
-
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
+            layout: bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: buffer.as_entire_binding(),
@@ -104,18 +112,60 @@ impl Resource {
         Self {
             camera,
             buffer,
-            bind_group_layout,
             bind_group,
+            view_width,
+            view_height,
+            width: width as _,
+            height: height as _,
         }
     }
 
     pub fn resize(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
-        let (clipping_width, clipping_height) = Self::get_contain_clipping(
-            consts::VIEW_WIDTH,
-            consts::VIEW_HEIGHT,
-            width as _,
-            height as _,
-        );
+        self.width = width as _;
+        self.height = height as _;
+        self.write(queue);
+    }
+
+    // the eye is moved opposite to the offset, and the view widened by the scale, so that the
+    // board at the origin appears shifted and shrunk within the whole view
+    pub fn set_view(
+        &mut self,
+        queue: &wgpu::Queue,
+        view_width: f32,
+        view_height: f32,
+        offset_x: f32,
+        offset_y: f32,
+        scale: f32,
+    ) {
+        self.view_width = view_width / scale;
+        self.view_height = view_height / scale;
+        self.camera.eye.x = -offset_x / scale;
+        self.camera.eye.y = -offset_y / scale;
+        self.write(queue);
+    }
+
+    pub fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let ndc_x = (x - self.camera.eye.x) / (self.camera.w_range * 0.5);
+        let ndc_y = (y - self.camera.eye.y) / (self.camera.h_range * 0.5);
+        (
+            (ndc_x + 1.0) * 0.5 * self.width,
+            (1.0 - ndc_y) * 0.5 * self.height,
+        )
+    }
+
+    // the inverse of to_screen
+    pub fn to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let ndc_x = x / self.width * 2.0 - 1.0;
+        let ndc_y = 1.0 - y / self.height * 2.0;
+        (
+            ndc_x * self.camera.w_range * 0.5 + self.camera.eye.x,
+            ndc_y * self.camera.h_range * 0.5 + self.camera.eye.y,
+        )
+    }
+
+    fn write(&mut self, queue: &wgpu::Queue) {
+        let (clipping_width, clipping_height) =
+            Self::get_contain_clipping(self.view_width, self.view_height, self.width, self.height);
         self.camera.w_range = clipping_width;
         self.camera.h_range = clipping_height;
         queue.write_buffer(
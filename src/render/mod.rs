@@ -13,7 +13,8 @@ pub struct RenderSystem {
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     window: Arc<winit::window::Window>,
-    camera_resource: camera::Resource,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    camera_resources: Vec<camera::Resource>,
     bg_pipeline: bg::Pipeline,
     block_pipeline: block::Pipeline,
     text_pipeline: text::Pipeline,
@@ -41,11 +42,16 @@ impl RenderSystem {
             .unwrap();
         surface.configure(&device, &config);
 
-        let camera_resource = camera::Resource::new(&device, config.width, config.height);
-        let bg_pipeline =
-            bg::Pipeline::new(&device, config.format, &camera_resource.bind_group_layout);
+        let camera_bind_group_layout = camera::Resource::create_bind_group_layout(&device);
+        let camera_resources = vec![camera::Resource::new(
+            &device,
+            &camera_bind_group_layout,
+            config.width,
+            config.height,
+        )];
+        let bg_pipeline = bg::Pipeline::new(&device, config.format, &camera_bind_group_layout);
         let block_pipeline =
-            block::Pipeline::new(&device, config.format, &camera_resource.bind_group_layout);
+            block::Pipeline::new(&device, config.format, &camera_bind_group_layout);
         let text_pipeline =
             text::Pipeline::new(&device, config.format, config.width, config.height);
 
@@ -55,7 +61,8 @@ impl RenderSystem {
             queue,
             config,
             window,
-            camera_resource,
+            camera_bind_group_layout,
+            camera_resources,
             bg_pipeline,
             block_pipeline,
             text_pipeline,
@@ -68,264 +75,524 @@ impl RenderSystem {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.bg_pipeline.render(
-            &self.device,
-            &self.queue,
-            &view,
-            &self.camera_resource.bind_group,
-        );
-
         match cx {
-            game::GameContext::Start(_) => {
-                self.text_pipeline.render(
-                    &self.device,
-                    &self.queue,
+            game::GameContext::Start(cx) => self.render_menu(&view, cx.menu, &cx.footer),
+            game::GameContext::Playing(cx) => {
+                let instances = Self::playing_instances(&cx);
+                let hud = match cx.level {
+                    Some(level) => format!(
+                        "SCORE: {}    LINES: {}    LEVEL: {}\nESCAPE: MENU",
+                        cx.score, cx.lines, level
+                    ),
+                    None => format!("SCORE: {}\nESCAPE: MENU", cx.score),
+                };
+                self.render_solo(&view, cx.dimensions, &instances, &hud, *cx.paused);
+            }
+            game::GameContext::End(cx) => {
+                let details = format!(
+                    "{}\nSCORE: {}\n\nPRESS RETURN TO RESTART\nPRESS ESCAPE TO QUIT",
+                    top_out_name(cx.top_out),
+                    cx.score
+                );
+                self.render_result(&view, cx.blocks, cx.dimensions, "GAME OVER", &details);
+            }
+            game::GameContext::Versus(cx) => {
+                let boards = cx
+                    .boards
+                    .iter()
+                    .enumerate()
+                    .map(|(index, board)| {
+                        let instances = Self::playing_instances(board);
+                        let title = format!("PLAYER {}\nSCORE: {}", index + 1, board.score);
+                        (instances, title)
+                    })
+                    .collect::<Vec<_>>();
+
+                self.render_boards(
                     &view,
-                    &[
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new("TETRIS")
-                                    .with_scale(consts::TEXT_SCALE * 4.0)
-                                    .with_color(consts::text_color::TEXT_PRIMARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 - consts::TEXT_SCALE * 4.0,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new("PRESS RETURN TO PLAY")
-                                    .with_scale(consts::TEXT_SCALE)
-                                    .with_color(consts::text_color::TEXT_PRIMARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 + consts::TEXT_SCALE,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new(
-                                    "ARROWS: MOVE    Z/X: ROTATE\nSPACE: HARD DROP    P: PAUSE",
-                                )
-                                .with_scale(consts::TEXT_SCALE * 0.75)
-                                .with_color(consts::text_color::TEXT_PRIMARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 + consts::TEXT_SCALE * 2.5,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
-                    ],
+                    cx.boards[0].dimensions,
+                    &boards,
+                    cx.paused.then_some("PAUSED"),
+                    None,
+                    "P1 A/D: MOVE  S: DOWN  W: HARD DROP  Q/E: ROTATE  R: 180  LSHIFT: HOLD\nP2 ARROWS: MOVE  UP: HARD DROP  ,/.: ROTATE  /: 180  RSHIFT: HOLD\nP: PAUSE",
                 );
             }
-            game::GameContext::Playing(cx) => {
-                let mut instances = vec![];
+            game::GameContext::VersusEnd(cx) => {
+                let boards = cx
+                    .boards
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (blocks, score))| {
+                        let instances = Self::board_instances(blocks, cx.dimensions, None, 0);
+                        let title = format!("PLAYER {}\nSCORE: {}", index + 1, score);
+                        (instances, title)
+                    })
+                    .collect::<Vec<_>>();
 
-                for col in 0..consts::VIEW_WIDTH as usize {
-                    let position = [
-                        col as f32 - consts::VIEW_WIDTH * 0.5,
-                        consts::MAX_STACK_HEIGHT as f32 - consts::VIEW_HEIGHT * 0.5,
-                        0.0,
-                    ];
-                    let color = consts::block_color::BG_MAX_STACK;
-                    instances.push(block::Instance { position, color });
-                }
+                let headline = match cx.outcome {
+                    game::versus_end::Outcome::Winner(winner) => {
+                        format!("PLAYER {} WINS", winner + 1)
+                    }
+                    game::versus_end::Outcome::Disconnected => "CONNECTION LOST".to_string(),
+                    game::versus_end::Outcome::Desynced => "DESYNC DETECTED".to_string(),
+                };
+                let subline = if *cx.online {
+                    "PRESS RETURN TO CONTINUE"
+                } else {
+                    "PRESS RETURN TO REMATCH\nPRESS ESCAPE TO QUIT"
+                };
+
+                self.render_boards(
+                    &view,
+                    cx.dimensions,
+                    &boards,
+                    Some(&headline),
+                    Some(subline),
+                    "",
+                );
+            }
+            game::GameContext::Lobby(cx) => {
+                let details = format!("{}\nPRESS ESCAPE TO CANCEL", cx.status);
+                self.render_result(
+                    &view,
+                    &[],
+                    &consts::DEFAULT_DIMENSIONS,
+                    "ONLINE VERSUS",
+                    &details,
+                );
+            }
+            game::GameContext::Online(cx) => {
+                let boards = cx
+                    .boards
+                    .iter()
+                    .enumerate()
+                    .map(|(index, board)| {
+                        let instances = Self::playing_instances(board);
+                        let name = if index == *cx.local {
+                            "YOU"
+                        } else {
+                            "OPPONENT"
+                        };
+                        let title = format!("{}\nSCORE: {}", name, board.score);
+                        (instances, title)
+                    })
+                    .collect::<Vec<_>>();
 
-                for (row, items) in cx.blocks.iter().enumerate() {
-                    for (col, item) in items.iter().enumerate() {
-                        if let Some(block_color) = item.as_ref() {
+                self.render_boards(
+                    &view,
+                    cx.boards[0].dimensions,
+                    &boards,
+                    None,
+                    cx.stalled.then_some("WAITING FOR OPPONENT"),
+                    "ARROWS: MOVE    Z/X: ROTATE    A: 180    SPACE: HARD DROP    C: HOLD",
+                );
+            }
+            game::GameContext::Royale(cx) => {
+                let dimensions = cx.board.dimensions;
+                self.layout_royale(cx.opponents.len(), dimensions);
+
+                let instances = Self::playing_instances(&cx.board);
+                self.render_board(&view, 0, dimensions, &instances);
+
+                for (index, opponent) in cx.opponents.iter().enumerate() {
+                    let mut instances = Self::board_instances(
+                        &opponent.blocks,
+                        dimensions,
+                        opponent.active_mino.as_ref(),
+                        opponent.pending_garbage,
+                    );
+
+                    if !opponent.alive {
+                        for instance in instances.iter_mut() {
+                            instance.color = consts::block_color::FG_GRAY;
+                        }
+                    }
+
+                    if opponent.targeted {
+                        for col in 0..dimensions.width {
                             let position = [
-                                col as f32 - consts::VIEW_WIDTH * 0.5,
-                                row as f32 - consts::VIEW_HEIGHT * 0.5,
+                                col as f32 - dimensions.width as f32 * 0.5,
+                                dimensions.visible_height as f32 * 0.5 + 0.5,
                                 0.0,
                             ];
-                            let color = consts::to_rgb(block_color);
+                            let color = consts::block_color::FG_PENDING;
                             instances.push(block::Instance { position, color });
                         }
                     }
-                }
 
-                if let Some(active_mino) = cx.active_mino.as_ref() {
-                    for (col, row) in active_mino.blocks.iter() {
-                        let position = [
-                            active_mino.x as f32 + *col as f32 - consts::VIEW_WIDTH * 0.5,
-                            active_mino.y as f32 + *row as f32 - consts::VIEW_HEIGHT * 0.5,
-                            0.0,
-                        ];
-                        let color = consts::to_rgb(&active_mino.template.color);
-                        instances.push(block::Instance { position, color })
-                    }
+                    self.render_board(&view, index + 1, dimensions, &instances);
                 }
 
-                self.block_pipeline.set_instances(&self.queue, &instances);
-                self.block_pipeline.render(
-                    &self.device,
-                    &self.queue,
-                    &view,
-                    &self.camera_resource.bind_group,
+                let strategy = match cx.strategy {
+                    game::royale::Strategy::Random => "RANDOM",
+                    game::royale::Strategy::Attackers => "ATTACKERS",
+                    game::royale::Strategy::KOs => "KOS",
+                    game::royale::Strategy::Badges => "BADGES",
+                };
+                let hud = format!(
+                    "SCORE: {}    KOS: {}    BADGES: {}\nREMAINING: {} / {}    TARGET: {}",
+                    cx.board.score,
+                    cx.kos,
+                    cx.badges,
+                    cx.remaining,
+                    cx.opponents.len() + 1,
+                    strategy
                 );
+                let position =
+                    self.camera_resources[0].to_screen(0.0, dimensions.visible_height as f32 * 0.5);
 
-                self.text_pipeline.render(
-                    &self.device,
-                    &self.queue,
-                    &view,
-                    &[
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new(&format!("SCORE: {}", cx.score))
-                                    .with_scale(consts::TEXT_SCALE)
-                                    .with_color(consts::text_color::TEXT_SECONDARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                consts::TEXT_SCALE * 0.5,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center),
-                            ),
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new(
-                                    "ARROWS: MOVE    Z/X: ROTATE\nSPACE: HARD DROP    P: PAUSE",
-                                )
-                                .with_scale(consts::TEXT_SCALE * 0.75)
+                let mut sections = vec![
+                    wgpu_glyph::Section::default()
+                        .add_text(
+                            wgpu_glyph::Text::new(&hud)
+                                .with_scale(consts::TEXT_SCALE)
                                 .with_color(consts::text_color::TEXT_SECONDARY),
+                        )
+                        .with_screen_position(position)
+                        .with_layout(
+                            wgpu_glyph::Layout::default()
+                                .h_align(wgpu_glyph::HorizontalAlign::Center)
+                                .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                        ),
+                    wgpu_glyph::Section::default()
+                        .add_text(
+                            wgpu_glyph::Text::new(
+                                "ARROWS: MOVE    Z/X: ROTATE    A: 180    SPACE: HARD DROP    C: HOLD    P: PAUSE\n1: RANDOM    2: ATTACKERS    3: KOS    4: BADGES",
                             )
-                            .with_screen_position((
-                                self.config.width as f32 - consts::TEXT_SCALE,
-                                consts::TEXT_SCALE * 0.5,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Right),
-                            ),
-                    ],
-                );
+                            .with_scale(consts::TEXT_SCALE * 0.75)
+                            .with_color(consts::text_color::TEXT_SECONDARY),
+                        )
+                        .with_screen_position((
+                            self.config.width as f32 * 0.5,
+                            self.config.height as f32 - consts::TEXT_SCALE * 0.5,
+                        ))
+                        .with_layout(
+                            wgpu_glyph::Layout::default()
+                                .h_align(wgpu_glyph::HorizontalAlign::Center)
+                                .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                        ),
+                ];
 
                 if *cx.paused {
-                    self.text_pipeline.render(
-                        &self.device,
-                        &self.queue,
-                        &view,
-                        &[wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new("PAUSED")
-                                    .with_scale(consts::TEXT_SCALE * 2.0)
-                                    .with_color(consts::text_color::TEXT_PRIMARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            )],
-                    );
+                    sections.push(self.paused_section());
                 }
+
+                self.text_pipeline
+                    .render(&self.device, &self.queue, &view, &sections);
             }
-            game::GameContext::End(cx) => {
-                let mut instances = vec![];
+            game::GameContext::RoyaleEnd(cx) => {
+                let headline = format!("#{} OF {}", cx.placement, cx.players);
+                let details = format!(
+                    "SCORE: {}    KOS: {}    BADGES: {}\n\nPRESS RETURN TO PLAY AGAIN\nPRESS ESCAPE TO QUIT",
+                    cx.score, cx.kos, cx.badges
+                );
+                self.render_result(&view, cx.blocks, cx.dimensions, &headline, &details);
+            }
+            game::GameContext::Cheese(cx) => {
+                let instances = Self::playing_instances(&cx.board);
+                let hud = format!(
+                    "TIME: {}    LINES LEFT: {}",
+                    format_duration(cx.elapsed),
+                    cx.remaining_lines
+                );
+                self.render_solo(
+                    &view,
+                    cx.board.dimensions,
+                    &instances,
+                    &hud,
+                    *cx.board.paused,
+                );
+            }
+            game::GameContext::CheeseEnd(cx) => {
+                let headline = if cx.cleared_lines == cx.lines {
+                    "FINISHED"
+                } else {
+                    "GAME OVER"
+                };
+                let details = format!(
+                    "{}TIME: {}\nLINES: {} / {}\n\nPRESS RETURN TO PLAY AGAIN\nPRESS ESCAPE TO QUIT",
+                    cx.top_out
+                        .map(|top_out| format!("{}\n", top_out_name(&top_out)))
+                        .unwrap_or_default(),
+                    format_duration(cx.elapsed),
+                    cx.cleared_lines,
+                    cx.lines
+                );
+                self.render_result(&view, cx.blocks, cx.dimensions, headline, &details);
+            }
+            game::GameContext::Survival(cx) => {
+                let dimensions = cx.board.dimensions;
+                let mut instances = Self::playing_instances(&cx.board);
 
-                for col in 0..consts::VIEW_WIDTH as usize {
+                // the warning row fills up under the board as the next row is about to rise
+                let rising_progress = cx.board.rising_progress.unwrap_or(0.0);
+                let warning_width = (rising_progress * dimensions.width as f32).ceil() as usize;
+                for col in 0..warning_width {
                     let position = [
-                        col as f32 - consts::VIEW_WIDTH * 0.5,
-                        consts::MAX_STACK_HEIGHT as f32 - consts::VIEW_HEIGHT * 0.5,
+                        col as f32 - dimensions.width as f32 * 0.5,
+                        -1.0 - dimensions.visible_height as f32 * 0.5,
                         0.0,
                     ];
-                    let color = consts::block_color::BG_MAX_STACK;
+                    let color = consts::block_color::FG_PENDING;
                     instances.push(block::Instance { position, color });
                 }
 
-                for (row, items) in cx.blocks.iter().enumerate() {
-                    for (col, item) in items.iter().enumerate() {
-                        if let Some(block_color) = item.as_ref() {
-                            let position = [
-                                col as f32 - consts::VIEW_WIDTH * 0.5,
-                                row as f32 - consts::VIEW_HEIGHT * 0.5,
-                                0.0,
-                            ];
-                            let color = consts::to_rgb(block_color);
-                            instances.push(block::Instance { position, color });
-                        }
+                let hud = format!(
+                    "TIME: {}    LINES: {}",
+                    format_duration(cx.elapsed),
+                    cx.board.lines
+                );
+                self.render_solo(&view, dimensions, &instances, &hud, *cx.board.paused);
+            }
+            game::GameContext::SurvivalEnd(cx) => {
+                let details = format!(
+                    "{}TIME: {}\nLINES: {}\n\nPRESS RETURN TO PLAY AGAIN\nPRESS ESCAPE TO QUIT",
+                    cx.top_out
+                        .map(|top_out| format!("{}\n", top_out_name(&top_out)))
+                        .unwrap_or_default(),
+                    format_duration(cx.elapsed),
+                    cx.lines
+                );
+                self.render_result(&view, cx.blocks, cx.dimensions, "GAME OVER", &details);
+            }
+            game::GameContext::Master(cx) => {
+                let instances = Self::playing_instances(&cx.board);
+                let hud = format!(
+                    "GRADE: {}    SCORE: {}    LEVEL: {}/{}    TIME: {}",
+                    cx.grade,
+                    cx.score,
+                    cx.level,
+                    cx.level_stop + 1,
+                    format_duration(cx.elapsed)
+                );
+                self.render_solo(
+                    &view,
+                    cx.board.dimensions,
+                    &instances,
+                    &hud,
+                    *cx.board.paused,
+                );
+            }
+            game::GameContext::MasterEnd(cx) => {
+                // each section split on its own line, the time into the run after it
+                let mut total = std::time::Duration::ZERO;
+                let sections = cx
+                    .sections
+                    .iter()
+                    .enumerate()
+                    .map(|(index, section)| {
+                        total += *section;
+                        format!(
+                            "{:03}-{:03}  {}  {}\n",
+                            index as u32 * consts::TGM_SECTION_LEVELS,
+                            ((index as u32 + 1) * consts::TGM_SECTION_LEVELS - 1)
+                                .min(consts::TGM_MAX_LEVEL),
+                            format_duration(section),
+                            format_duration(&total)
+                        )
+                    })
+                    .collect::<String>();
+                let details = format!(
+                    "{}LEVEL: {}\nTIME: {}\n\n{}\nPRESS RETURN TO PLAY AGAIN\nPRESS ESCAPE TO QUIT",
+                    cx.top_out
+                        .map(|top_out| format!("{}\n", top_out_name(&top_out)))
+                        .unwrap_or_default(),
+                    cx.level,
+                    format_duration(cx.elapsed),
+                    sections
+                );
+                let headline = format!("GRADE {}", cx.grade);
+                self.render_result(&view, cx.blocks, cx.dimensions, &headline, &details);
+            }
+            game::GameContext::Practice(cx) => {
+                let instances = Self::playing_instances(&cx.board);
+                let hud = format!(
+                    "UNDO: {}    REDO: {}    HOLD: {}\nU: UNDO  Y: REDO  Q/W: NEXT PIECE  I: INFINITE HOLD  S: SAVE  R: RESET",
+                    cx.undo,
+                    cx.redo,
+                    if cx.infinite_hold { "INFINITE" } else { "ONCE" }
+                );
+                self.render_solo(
+                    &view,
+                    cx.board.dimensions,
+                    &instances,
+                    &hud,
+                    *cx.board.paused,
+                );
+            }
+            game::GameContext::PuzzleSelect(cx) => self.render_menu(
+                &view,
+                cx.menu,
+                "LEFT/RIGHT: PACK    UP/DOWN: PUZZLE\nRETURN: PLAY    ESCAPE: QUIT",
+            ),
+            game::GameContext::Puzzle(cx) => {
+                let instances = Self::playing_instances(&cx.board);
+                let hud = format!(
+                    "{}    GOAL: {}    PIECES LEFT: {}\nR: RETRY",
+                    cx.name.to_uppercase(),
+                    goal_name(&cx.goal),
+                    cx.pieces_left
+                );
+                self.render_solo(
+                    &view,
+                    cx.board.dimensions,
+                    &instances,
+                    &hud,
+                    *cx.board.paused,
+                );
+            }
+            game::GameContext::PuzzleEnd(cx) => {
+                let headline = match cx.verdict {
+                    game::puzzles::Verdict::Solved => "SOLVED",
+                    game::puzzles::Verdict::Failed => "FAILED",
+                };
+                let next = match (cx.verdict, cx.has_next) {
+                    (game::puzzles::Verdict::Failed, _) => "PRESS RETURN TO RETRY\n",
+                    (_, true) => "PRESS RETURN FOR THE NEXT PUZZLE\n",
+                    (_, false) => "",
+                };
+                let details = format!("{}\n\n{}PRESS ESCAPE TO QUIT", cx.name.to_uppercase(), next);
+                self.render_result(&view, cx.blocks, cx.dimensions, headline, &details);
+            }
+            game::GameContext::Scripted(cx) => {
+                let instances = Self::playing_instances(&cx.board);
+                // the script's text stands in for the score once it sets any
+                let hud = if cx.hud.is_empty() {
+                    format!("SCORE: {}    LINES: {}", cx.board.score, cx.board.lines)
+                } else {
+                    cx.hud.to_uppercase()
+                };
+                self.render_solo(
+                    &view,
+                    cx.board.dimensions,
+                    &instances,
+                    &hud,
+                    *cx.board.paused,
+                );
+            }
+            game::GameContext::ScriptEnd(cx) => {
+                let details = format!(
+                    "{}\nSCORE: {}\n\nPRESS RETURN TO PLAY AGAIN\nPRESS ESCAPE TO QUIT",
+                    cx.message.to_uppercase(),
+                    cx.score
+                );
+                self.render_result(&view, cx.blocks, cx.dimensions, "GAME OVER", &details);
+            }
+            game::GameContext::Editor(cx) => {
+                let dimensions = cx.dimensions;
+                let (board_width, board_height) = Self::board_size(dimensions);
+                let side_width = (consts::EDITOR_BUTTON_WIDTH + 2) as f32;
+                self.layout(
+                    board_width + side_width * 2.0,
+                    board_height + 4.0,
+                    &[(0.0, 0.0, 1.0)],
+                );
+
+                let to_position = |(col, row): (i32, i32)| {
+                    [
+                        col as f32 - board_width * 0.5,
+                        row as f32 - board_height * 0.5,
+                        0.0,
+                    ]
+                };
+
+                // the palette right of the board, the selected color marked beside it
+                let mut instances = Self::board_instances(cx.blocks, dimensions, None, 0);
+                for (index, color) in game::editor::PALETTE.iter().enumerate() {
+                    let (col, row) = game::editor::palette_cell(dimensions, index);
+                    instances.push(block::Instance {
+                        position: to_position((col, row)),
+                        color: consts::to_rgb(color),
+                    });
+                    if index == cx.color {
+                        instances.push(block::Instance {
+                            position: to_position((col + 1, row)),
+                            color: consts::block_color::FG_CLEARING,
+                        });
                     }
                 }
+                self.render_board(&view, 0, dimensions, &instances);
 
-                self.block_pipeline.set_instances(&self.queue, &instances);
-                self.block_pipeline.render(
-                    &self.device,
-                    &self.queue,
-                    &view,
-                    &self.camera_resource.bind_group,
+                let labels = game::editor::BUTTONS
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (button, label))| {
+                        let [x, y, _] = to_position(game::editor::button_cell(dimensions, index));
+                        let selected = *button == game::editor::Button::Tool(cx.tool);
+                        let label = format!("{}{}", if selected { "> " } else { "" }, label);
+                        (self.camera_resources[0].to_screen(x, y + 0.5), label)
+                    })
+                    .collect::<Vec<_>>();
+                let hud = format!(
+                    "QUEUE: {}    HOLD: {}\n{}",
+                    if cx.queue.is_empty() { "-" } else { cx.queue },
+                    cx.hold.map_or("-".to_string(), String::from),
+                    cx.message.unwrap_or_default()
                 );
 
-                self.text_pipeline.render(
-                    &self.device,
-                    &self.queue,
-                    &view,
-                    &[
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new("GAME OVER")
-                                    .with_scale(consts::TEXT_SCALE * 2.0)
-                                    .with_color(consts::text_color::TEXT_PRIMARY),
-                            )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 - consts::TEXT_SCALE * 2.0,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
+                let mut sections = labels
+                    .iter()
+                    .map(|(position, label)| {
                         wgpu_glyph::Section::default()
                             .add_text(
-                                wgpu_glyph::Text::new(&format!("SCORE: {}", cx.score))
+                                wgpu_glyph::Text::new(label)
                                     .with_scale(consts::TEXT_SCALE)
                                     .with_color(consts::text_color::TEXT_PRIMARY),
                             )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 + consts::TEXT_SCALE,
-                            ))
+                            .with_screen_position(*position)
                             .with_layout(
                                 wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
                                     .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
-                        wgpu_glyph::Section::default()
-                            .add_text(
-                                wgpu_glyph::Text::new("PRESS RETURN TO RESTART")
-                                    .with_scale(consts::TEXT_SCALE)
-                                    .with_color(consts::text_color::TEXT_PRIMARY),
                             )
-                            .with_screen_position((
-                                self.config.width as f32 * 0.5,
-                                self.config.height as f32 * 0.5 + consts::TEXT_SCALE * 2.5,
-                            ))
-                            .with_layout(
-                                wgpu_glyph::Layout::default()
-                                    .h_align(wgpu_glyph::HorizontalAlign::Center)
-                                    .v_align(wgpu_glyph::VerticalAlign::Center),
-                            ),
-                    ],
+                    })
+                    .collect::<Vec<_>>();
+                sections.push(
+                    wgpu_glyph::Section::default()
+                        .add_text(
+                            wgpu_glyph::Text::new(&hud)
+                                .with_scale(consts::TEXT_SCALE)
+                                .with_color(consts::text_color::TEXT_SECONDARY),
+                        )
+                        .with_screen_position((
+                            self.config.width as f32 * 0.5,
+                            consts::TEXT_SCALE * 0.5,
+                        ))
+                        .with_layout(
+                            wgpu_glyph::Layout::default()
+                                .h_align(wgpu_glyph::HorizontalAlign::Center),
+                        ),
+                );
+                sections.push(
+                    wgpu_glyph::Section::default()
+                        .add_text(
+                            wgpu_glyph::Text::new(
+                                "LEFT CLICK: USE TOOL    RIGHT CLICK: ERASE\nIOSZJLT: ADD TO QUEUE    BACKSPACE: REMOVE FROM QUEUE\nRETURN: PLAY    ESCAPE: QUIT",
+                            )
+                            .with_scale(consts::TEXT_SCALE * 0.75)
+                            .with_color(consts::text_color::TEXT_SECONDARY),
+                        )
+                        .with_screen_position((
+                            self.config.width as f32 * 0.5,
+                            self.config.height as f32 - consts::TEXT_SCALE * 0.5,
+                        ))
+                        .with_layout(
+                            wgpu_glyph::Layout::default()
+                                .h_align(wgpu_glyph::HorizontalAlign::Center)
+                                .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                        ),
+                );
+
+                self.text_pipeline
+                    .render(&self.device, &self.queue, &view, &sections);
+            }
+            game::GameContext::Classic(cx) => {
+                let headline = format!("LEVEL {}", cx.level);
+                self.render_result(
+                    &view,
+                    &[],
+                    &consts::DEFAULT_DIMENSIONS,
+                    &headline,
+                    "LEFT/RIGHT: LEVEL    UP/DOWN: 10 LEVELS\n\nPRESS RETURN TO PLAY\nPRESS ESCAPE TO QUIT",
                 );
             }
         }
@@ -333,13 +600,530 @@ impl RenderSystem {
         output.present();
     }
 
+    // every board is seen through its own camera, placed at (x, y) and scaled within the view
+    fn layout(&mut self, view_width: f32, view_height: f32, placements: &[(f32, f32, f32)]) {
+        while self.camera_resources.len() < placements.len() {
+            self.camera_resources.push(camera::Resource::new(
+                &self.device,
+                &self.camera_bind_group_layout,
+                self.config.width,
+                self.config.height,
+            ));
+        }
+
+        for (camera_resource, (x, y, scale)) in self.camera_resources.iter_mut().zip(placements) {
+            camera_resource.set_view(&self.queue, view_width, view_height, *x, *y, *scale);
+        }
+    }
+
+    // boards are laid out side by side, each fitting its visible rows
+    fn layout_boards(&mut self, count: usize, dimensions: &consts::Dimensions) {
+        let (board_width, board_height) = Self::board_size(dimensions);
+        let (stride, view_width, view_height) = if count <= 1 {
+            (0.0, board_width, board_height)
+        } else {
+            let stride = board_width + consts::VERSUS_BOARD_GAP;
+            (
+                stride,
+                stride * count as f32,
+                board_height + consts::VERSUS_BOARD_GAP,
+            )
+        };
+
+        let placements = (0..count)
+            .map(|index| {
+                (
+                    (index as f32 - (count as f32 - 1.0) * 0.5) * stride,
+                    0.0,
+                    1.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        self.layout(view_width, view_height, &placements);
+    }
+
+    // the main board sits in the middle with the miniature boards split into a grid on each side
+    fn layout_royale(&mut self, opponents: usize, dimensions: &consts::Dimensions) {
+        let (board_width, board_height) = Self::board_size(dimensions);
+        let side_offset = (board_width + consts::ROYALE_SIDE_WIDTH) * 0.5 + 1.0;
+        let view_width = side_offset * 2.0 + consts::ROYALE_SIDE_WIDTH;
+        let view_height = board_height + consts::VERSUS_BOARD_GAP;
+
+        let cell_width = board_width + 2.0;
+        let cell_height = board_height + 2.0;
+
+        let mut placements = vec![(0.0, 0.0, 1.0)];
+
+        let left = opponents.div_ceil(2);
+        for (count, center_x) in [(left, -side_offset), (opponents - left, side_offset)] {
+            let Some((cols, rows, scale)) = (1..=count)
+                .map(|cols| {
+                    let rows = count.div_ceil(cols);
+                    let scale = (consts::ROYALE_SIDE_WIDTH / (cols as f32 * cell_width))
+                        .min(board_height / (rows as f32 * cell_height));
+                    (cols, rows, scale)
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2))
+            else {
+                continue;
+            };
+
+            let grid_width = cols as f32 * cell_width * scale;
+            let grid_height = rows as f32 * cell_height * scale;
+            for index in 0..count {
+                let (col, row) = (index % cols, index / cols);
+                let x = center_x - grid_width * 0.5 + (col as f32 + 0.5) * cell_width * scale;
+                let y = grid_height * 0.5 - (row as f32 + 0.5) * cell_height * scale;
+                placements.push((x, y, scale));
+            }
+        }
+
+        self.layout(view_width, view_height, &placements);
+    }
+
+    // the visible part of a board, centered on the origin
+    fn board_size(dimensions: &consts::Dimensions) -> (f32, f32) {
+        (dimensions.width as f32, dimensions.visible_height as f32)
+    }
+
+    // a playing board along with the lines it is clearing and its held piece beside it
+    fn playing_instances(board: &game::playing::GameContext) -> Vec<block::Instance> {
+        let dimensions = board.dimensions;
+        let (board_width, board_height) = Self::board_size(dimensions);
+        let mut instances = Self::board_instances(
+            board.blocks,
+            dimensions,
+            board.active_mino.as_ref(),
+            board.pending_garbage.iter().sum(),
+        );
+
+        for row in board.clearing_lines.iter() {
+            for col in 0..dimensions.width {
+                let position = [
+                    col as f32 - board_width * 0.5,
+                    *row as f32 - board_height * 0.5,
+                    0.0,
+                ];
+                let color = consts::block_color::FG_CLEARING;
+                instances.push(block::Instance { position, color });
+            }
+        }
+
+        if let Some(held_mino) = board.held_mino.as_ref() {
+            for (col, row) in held_mino.blocks.iter() {
+                let position = [
+                    *col as f32 - board_width * 0.5 - 7.0,
+                    *row as f32 + dimensions.stack_height as f32 - 4.0 - board_height * 0.5,
+                    0.0,
+                ];
+                let color = consts::to_rgb(&held_mino.color);
+                instances.push(block::Instance { position, color });
+            }
+        }
+
+        for (index, next_mino) in board.next_minos.iter().enumerate() {
+            for (col, row) in next_mino.blocks.iter() {
+                let position = [
+                    *col as f32 + board_width * 0.5 + 3.0,
+                    *row as f32 + dimensions.stack_height as f32
+                        - 4.0
+                        - index as f32 * 3.0
+                        - board_height * 0.5,
+                    0.0,
+                ];
+                let color = consts::to_rgb(&next_mino.color);
+                instances.push(block::Instance { position, color });
+            }
+        }
+
+        instances
+    }
+
+    fn board_instances(
+        blocks: &[Vec<Option<consts::BlockColor>>],
+        dimensions: &consts::Dimensions,
+        active_mino: Option<&game::playing::Mino>,
+        pending_garbage: i32,
+    ) -> Vec<block::Instance> {
+        let (board_width, board_height) = Self::board_size(dimensions);
+        let mut instances = vec![];
+
+        for col in 0..dimensions.width {
+            let position = [
+                col as f32 - board_width * 0.5,
+                dimensions.stack_height as f32 - board_height * 0.5,
+                0.0,
+            ];
+            let color = consts::block_color::BG_MAX_STACK;
+            instances.push(block::Instance { position, color });
+        }
+
+        for (row, items) in blocks.iter().enumerate() {
+            for (col, item) in items.iter().enumerate() {
+                if let Some(block_color) = item.as_ref() {
+                    let position = [
+                        col as f32 - board_width * 0.5,
+                        row as f32 - board_height * 0.5,
+                        0.0,
+                    ];
+                    let color = consts::to_rgb(block_color);
+                    instances.push(block::Instance { position, color });
+                }
+            }
+        }
+
+        if let Some(active_mino) = active_mino {
+            for (col, row) in active_mino.blocks.iter() {
+                let position = [
+                    active_mino.x as f32 + *col as f32 - board_width * 0.5,
+                    active_mino.y as f32 + *row as f32 - board_height * 0.5,
+                    0.0,
+                ];
+                let color = consts::to_rgb(&active_mino.color);
+                instances.push(block::Instance { position, color })
+            }
+        }
+
+        for row in 0..pending_garbage.min(dimensions.stack_height) {
+            let position = [
+                -board_width * 0.5 - 1.5,
+                row as f32 - board_height * 0.5,
+                0.0,
+            ];
+            let color = consts::block_color::FG_PENDING;
+            instances.push(block::Instance { position, color });
+        }
+
+        instances
+    }
+
+    fn render_board(
+        &mut self,
+        view: &wgpu::TextureView,
+        index: usize,
+        dimensions: &consts::Dimensions,
+        instances: &[block::Instance],
+    ) {
+        let bind_group = &self.camera_resources[index].bind_group;
+
+        let (board_width, board_height) = Self::board_size(dimensions);
+        self.bg_pipeline
+            .set_size(&self.queue, board_width, board_height);
+
+        self.bg_pipeline
+            .render(&self.device, &self.queue, view, bind_group, index == 0);
+
+        if !instances.is_empty() {
+            self.block_pipeline.set_instances(&self.queue, instances);
+            self.block_pipeline
+                .render(&self.device, &self.queue, view, bind_group);
+        }
+    }
+
+    fn render_boards(
+        &mut self,
+        view: &wgpu::TextureView,
+        dimensions: &consts::Dimensions,
+        boards: &[(Vec<block::Instance>, String)],
+        headline: Option<&str>,
+        subline: Option<&str>,
+        footer: &str,
+    ) {
+        self.layout_boards(boards.len(), dimensions);
+
+        let mut sections = vec![];
+
+        for (index, (instances, title)) in boards.iter().enumerate() {
+            self.render_board(view, index, dimensions, instances);
+
+            let position =
+                self.camera_resources[index].to_screen(0.0, dimensions.visible_height as f32 * 0.5);
+            sections.push(
+                wgpu_glyph::Section::default()
+                    .add_text(
+                        wgpu_glyph::Text::new(title)
+                            .with_scale(consts::TEXT_SCALE)
+                            .with_color(consts::text_color::TEXT_SECONDARY),
+                    )
+                    .with_screen_position(position)
+                    .with_layout(
+                        wgpu_glyph::Layout::default()
+                            .h_align(wgpu_glyph::HorizontalAlign::Center)
+                            .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                    ),
+            );
+        }
+
+        if let Some(headline) = headline {
+            sections.push(
+                wgpu_glyph::Section::default()
+                    .add_text(
+                        wgpu_glyph::Text::new(headline)
+                            .with_scale(consts::TEXT_SCALE * 2.0)
+                            .with_color(consts::text_color::TEXT_PRIMARY),
+                    )
+                    .with_screen_position((
+                        self.config.width as f32 * 0.5,
+                        self.config.height as f32 * 0.5 - consts::TEXT_SCALE * 2.0,
+                    ))
+                    .with_layout(
+                        wgpu_glyph::Layout::default()
+                            .h_align(wgpu_glyph::HorizontalAlign::Center)
+                            .v_align(wgpu_glyph::VerticalAlign::Center),
+                    ),
+            );
+        }
+
+        if let Some(subline) = subline {
+            sections.push(
+                wgpu_glyph::Section::default()
+                    .add_text(
+                        wgpu_glyph::Text::new(subline)
+                            .with_scale(consts::TEXT_SCALE)
+                            .with_color(consts::text_color::TEXT_PRIMARY),
+                    )
+                    .with_screen_position((
+                        self.config.width as f32 * 0.5,
+                        self.config.height as f32 * 0.5 + consts::TEXT_SCALE,
+                    ))
+                    .with_layout(
+                        wgpu_glyph::Layout::default()
+                            .h_align(wgpu_glyph::HorizontalAlign::Center)
+                            .v_align(wgpu_glyph::VerticalAlign::Center),
+                    ),
+            );
+        }
+
+        sections.push(
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(footer)
+                        .with_scale(consts::TEXT_SCALE * 0.75)
+                        .with_color(consts::text_color::TEXT_SECONDARY),
+                )
+                .with_screen_position((
+                    self.config.width as f32 * 0.5,
+                    self.config.height as f32 - consts::TEXT_SCALE * 0.5,
+                ))
+                .with_layout(
+                    wgpu_glyph::Layout::default()
+                        .h_align(wgpu_glyph::HorizontalAlign::Center)
+                        .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                ),
+        );
+
+        self.text_pipeline
+            .render(&self.device, &self.queue, view, &sections);
+    }
+
+    // a single board with a heads-up line on top, as the solo modes are laid out, leaving a row
+    // below the board for warnings
+    fn render_solo(
+        &mut self,
+        view: &wgpu::TextureView,
+        dimensions: &consts::Dimensions,
+        instances: &[block::Instance],
+        hud: &str,
+        paused: bool,
+    ) {
+        let (board_width, board_height) = Self::board_size(dimensions);
+        self.layout(board_width, board_height + 2.0, &[(0.0, 1.0, 1.0)]);
+        self.render_board(view, 0, dimensions, instances);
+
+        let mut sections = vec![
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(hud)
+                        .with_scale(consts::TEXT_SCALE)
+                        .with_color(consts::text_color::TEXT_SECONDARY),
+                )
+                .with_screen_position((self.config.width as f32 * 0.5, consts::TEXT_SCALE * 0.5))
+                .with_layout(
+                    wgpu_glyph::Layout::default().h_align(wgpu_glyph::HorizontalAlign::Center),
+                ),
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(
+                        "ARROWS: MOVE    Z/X: ROTATE    A: 180\nSPACE: HARD DROP    C: HOLD    P: PAUSE",
+                    )
+                    .with_scale(consts::TEXT_SCALE * 0.75)
+                    .with_color(consts::text_color::TEXT_SECONDARY),
+                )
+                .with_screen_position((
+                    self.config.width as f32 - consts::TEXT_SCALE,
+                    consts::TEXT_SCALE * 0.5,
+                ))
+                .with_layout(
+                    wgpu_glyph::Layout::default().h_align(wgpu_glyph::HorizontalAlign::Right),
+                ),
+        ];
+
+        if paused {
+            sections.push(self.paused_section());
+        }
+
+        self.text_pipeline
+            .render(&self.device, &self.queue, view, &sections);
+    }
+
+    // drawn over the middle of the window while a game is paused
+    fn paused_section(&self) -> wgpu_glyph::Section<'static> {
+        wgpu_glyph::Section::default()
+            .add_text(
+                wgpu_glyph::Text::new("PAUSED")
+                    .with_scale(consts::TEXT_SCALE * 2.0)
+                    .with_color(consts::text_color::TEXT_PRIMARY),
+            )
+            .with_screen_position((
+                self.config.width as f32 * 0.5,
+                self.config.height as f32 * 0.5,
+            ))
+            .with_layout(
+                wgpu_glyph::Layout::default()
+                    .h_align(wgpu_glyph::HorizontalAlign::Center)
+                    .v_align(wgpu_glyph::VerticalAlign::Center),
+            )
+    }
+
+    // a menu over an empty board, the selected item marked and the disabled ones dimmed, with the
+    // keys it takes along the bottom
+    fn render_menu(&mut self, view: &wgpu::TextureView, menu: &game::menu::Menu, footer: &str) {
+        let dimensions = consts::DEFAULT_DIMENSIONS;
+        self.layout_boards(1, &dimensions);
+        self.render_board(view, 0, &dimensions, &[]);
+
+        let camera_resource = &self.camera_resources[0];
+        let labels = menu
+            .visible()
+            .map(|index| {
+                let item = &menu.items[index];
+                let (x, y) = menu.item_position(index);
+                let mut label = match &item.value {
+                    Some(value) => format!("{}: {}", item.label, value),
+                    None => item.label.clone(),
+                };
+                if index == menu.selected && item.enabled {
+                    label = format!("> {} <", label);
+                }
+                let color = if item.enabled {
+                    consts::text_color::TEXT_PRIMARY
+                } else {
+                    consts::text_color::TEXT_SECONDARY
+                };
+                (camera_resource.to_screen(x, y), label, color)
+            })
+            .collect::<Vec<_>>();
+        let (x, y) = menu.title_position();
+        let title_position = camera_resource.to_screen(x, y);
+
+        let mut sections = vec![
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(&menu.title)
+                        .with_scale(consts::TEXT_SCALE * 2.0)
+                        .with_color(consts::text_color::TEXT_PRIMARY),
+                )
+                .with_screen_position(title_position)
+                .with_layout(
+                    wgpu_glyph::Layout::default()
+                        .h_align(wgpu_glyph::HorizontalAlign::Center)
+                        .v_align(wgpu_glyph::VerticalAlign::Center),
+                ),
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(footer)
+                        .with_scale(consts::TEXT_SCALE * 0.75)
+                        .with_color(consts::text_color::TEXT_SECONDARY),
+                )
+                .with_screen_position((
+                    self.config.width as f32 * 0.5,
+                    self.config.height as f32 - consts::TEXT_SCALE * 0.5,
+                ))
+                .with_layout(
+                    wgpu_glyph::Layout::default()
+                        .h_align(wgpu_glyph::HorizontalAlign::Center)
+                        .v_align(wgpu_glyph::VerticalAlign::Bottom),
+                ),
+        ];
+        sections.extend(labels.iter().map(|(position, label, color)| {
+            wgpu_glyph::Section::default()
+                .add_text(
+                    wgpu_glyph::Text::new(label)
+                        .with_scale(consts::TEXT_SCALE)
+                        .with_color(*color),
+                )
+                .with_screen_position(*position)
+                .with_layout(
+                    wgpu_glyph::Layout::default()
+                        .h_align(wgpu_glyph::HorizontalAlign::Center)
+                        .v_align(wgpu_glyph::VerticalAlign::Center),
+                )
+        }));
+
+        self.text_pipeline
+            .render(&self.device, &self.queue, view, &sections);
+    }
+
+    // the final board of a solo mode behind its result
+    fn render_result(
+        &mut self,
+        view: &wgpu::TextureView,
+        blocks: &[Vec<Option<consts::BlockColor>>],
+        dimensions: &consts::Dimensions,
+        headline: &str,
+        details: &str,
+    ) {
+        self.layout_boards(1, dimensions);
+        let instances = Self::board_instances(blocks, dimensions, None, 0);
+        self.render_board(view, 0, dimensions, &instances);
+
+        self.text_pipeline.render(
+            &self.device,
+            &self.queue,
+            view,
+            &[
+                wgpu_glyph::Section::default()
+                    .add_text(
+                        wgpu_glyph::Text::new(headline)
+                            .with_scale(consts::TEXT_SCALE * 2.0)
+                            .with_color(consts::text_color::TEXT_PRIMARY),
+                    )
+                    .with_screen_position((
+                        self.config.width as f32 * 0.5,
+                        self.config.height as f32 * 0.5 - consts::TEXT_SCALE * 2.0,
+                    ))
+                    .with_layout(
+                        wgpu_glyph::Layout::default()
+                            .h_align(wgpu_glyph::HorizontalAlign::Center)
+                            .v_align(wgpu_glyph::VerticalAlign::Center),
+                    ),
+                wgpu_glyph::Section::default()
+                    .add_text(
+                        wgpu_glyph::Text::new(details)
+                            .with_scale(consts::TEXT_SCALE)
+                            .with_color(consts::text_color::TEXT_PRIMARY),
+                    )
+                    .with_screen_position((
+                        self.config.width as f32 * 0.5,
+                        self.config.height as f32 * 0.5 + consts::TEXT_SCALE,
+                    ))
+                    .with_layout(
+                        wgpu_glyph::Layout::default()
+                            .h_align(wgpu_glyph::HorizontalAlign::Center)
+                            .v_align(wgpu_glyph::VerticalAlign::Top),
+                    ),
+            ],
+        );
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if 0 < new_size.width && 0 < new_size.height {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
-            self.camera_resource
-                .resize(&self.queue, self.config.width, self.config.height);
+            for camera_resource in self.camera_resources.iter_mut() {
+                camera_resource.resize(&self.queue, self.config.width, self.config.height);
+            }
             self.text_pipeline
                 .resize(self.config.width, self.config.height);
         }
@@ -349,7 +1133,45 @@ impl RenderSystem {
         self.window.request_redraw();
     }
 
+    // a point on the window in blocks from the center of the first board
+    pub fn to_board(&self, position: winit::dpi::PhysicalPosition<f64>) -> (f32, f32) {
+        self.camera_resources
+            .first()
+            .map_or((0.0, 0.0), |camera_resource| {
+                camera_resource.to_world(position.x as f32, position.y as f32)
+            })
+    }
+
     pub fn match_id(&self, id: winit::window::WindowId) -> bool {
         self.window.id() == id
     }
 }
+
+fn top_out_name(top_out: &game::playing::TopOut) -> &'static str {
+    match top_out {
+        game::playing::TopOut::BlockOut => "BLOCK OUT",
+        game::playing::TopOut::LockOut => "LOCK OUT",
+        game::playing::TopOut::PartialLockOut => "PARTIAL LOCK OUT",
+        game::playing::TopOut::GarbageOut => "TOPPED OUT BY GARBAGE",
+    }
+}
+
+fn goal_name(goal: &game::puzzles::Goal) -> String {
+    match goal {
+        game::puzzles::Goal::Lines(lines) => format!("CLEAR {} LINES", lines),
+        game::puzzles::Goal::PerfectClear => "PERFECT CLEAR".to_string(),
+        game::puzzles::Goal::TSpinTriple => "T-SPIN TRIPLE".to_string(),
+        game::puzzles::Goal::NoHoles => "NO HOLES".to_string(),
+    }
+}
+
+// minutes, seconds and milliseconds as timed modes show them
+fn format_duration(duration: &std::time::Duration) -> String {
+    let millis = duration.as_millis();
+    format!(
+        "{}:{:02}.{:03}",
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}